@@ -1,14 +1,31 @@
 use crate::{
     config::{
-        filters::{Filter, ReleaseChannel},
-        structs::{ModIdentifier, ModLoader, Profile},
+        filters::{Filter, ProfileParameters as _, ReleaseChannel},
+        github_hosts::{self, GitHubClient},
+        gitlab_hosts::{self, GITLAB_PREFIX},
+        structs::{ContentType, Environment, ModIdentifier, ModLoader, Profile},
+        Context,
     },
+    game_version::{self, Manifest},
+    gitlab,
     iter_ext::IterExt as _,
-    upgrade::{check, Metadata},
-    CURSEFORGE_API, GITHUB_API, MODRINTH_API,
+    upgrade::{
+        cf_file_environment,
+        check::{self, CompiledFilters},
+        from_gl_releases, inspect,
+        mod_downloadable::from_gh_releases_inspected,
+        AssetKind, Metadata,
+    },
+    CURSEFORGE_API, MODRINTH_API,
+};
+use chrono::{DateTime, Utc};
+use octocrab::Octocrab;
+use regex::Regex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
 };
-use serde::Deserialize;
-use std::{collections::HashMap, str::FromStr};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -20,16 +37,28 @@ pub enum Error {
     DistributionDenied,
     #[error("The project has already been added")]
     AlreadyAdded,
+    #[error("The asset's filename regex is invalid: {0}")]
+    AssetRegex(#[from] regex::Error),
     #[error("The project is not compatible because {_0}")]
     Incompatible(#[from] check::Error),
     #[error("The project does not exist")]
     DoesNotExist,
-    #[error("The project is not a mod")]
+    #[error("The project is not a mod, plugin, resource pack, shader, or data pack")]
     NotAMod,
+    #[error(
+        "Data packs are installed to a world, so the profile's world has to be set to add them"
+    )]
+    NoWorld,
     #[error("GitHub: {0}")]
     GitHubError(String),
     #[error("GitHub: {0:#?}")]
     OctocrabError(#[from] octocrab::Error),
+    #[error(transparent)]
+    GitHubHost(#[from] github_hosts::Error),
+    #[error("GitLab: {0}")]
+    GitLabError(gitlab::Error),
+    #[error(transparent)]
+    GitLabHost(#[from] gitlab_hosts::Error),
     #[error("Modrinth: {0}")]
     ModrinthError(#[from] ferinth::Error),
     #[error("CurseForge: {0}")]
@@ -37,9 +66,26 @@ pub enum Error {
 }
 type Result<T> = std::result::Result<T, Error>;
 
+impl From<gitlab::Error> for Error {
+    fn from(error: gitlab::Error) -> Self {
+        match error {
+            gitlab::Error::NotFound => Error::DoesNotExist,
+            error => Error::GitLabError(error),
+        }
+    }
+}
+
+/// A GraphQL query with the values of its variables
+#[derive(Serialize, Debug)]
+struct GraphQlRequest {
+    query: String,
+    variables: HashMap<String, Option<String>>,
+}
+
 #[derive(Deserialize, Debug)]
-struct GraphQlResponse {
-    data: HashMap<String, Option<ResponseData>>,
+struct GraphQlResponse<T> {
+    /// The data of the fields by their aliases, which is missing or null if the whole query failed
+    data: Option<HashMap<String, Option<T>>>,
     #[serde(default)]
     errors: Vec<GraphQLError>,
 }
@@ -47,11 +93,30 @@ struct GraphQlResponse {
 #[derive(Deserialize, Debug)]
 struct GraphQLError {
     #[serde(rename = "type")]
-    type_: String,
-    path: Vec<String>,
+    type_: Option<String>,
+    /// The path of the field that caused the error, starting with its alias
+    #[serde(default)]
+    path: Vec<serde_json::Value>,
     message: String,
 }
 
+impl GraphQLError {
+    /// The index in the batch of the field that caused this error, from its alias (e.g. `_3`)
+    fn alias_index(&self) -> Option<usize> {
+        self.path.first()?.as_str()?.strip_prefix('_')?.parse().ok()
+    }
+}
+
+impl From<GraphQLError> for Error {
+    fn from(error: GraphQLError) -> Self {
+        if error.type_.as_deref() == Some("NOT_FOUND") {
+            Error::DoesNotExist
+        } else {
+            Error::GitHubError(error.message)
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 struct ResponseData {
     owner: OwnerData,
@@ -65,31 +130,355 @@ struct OwnerData {
 #[derive(Deserialize, Debug)]
 struct ReleaseConnection {
     nodes: Vec<Release>,
+    #[serde(rename = "pageInfo")]
+    page_info: PageInfo,
+}
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
 }
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct Release {
+    id: String,
     name: String,
+    tag_name: String,
     description: String,
     is_prerelease: bool,
+    created_at: DateTime<Utc>,
+    published_at: Option<DateTime<Utc>>,
     release_assets: ReleaseAssetConnection,
 }
 #[derive(Deserialize, Debug)]
 struct ReleaseAssetConnection {
     nodes: Vec<ReleaseAsset>,
+    #[serde(rename = "pageInfo")]
+    page_info: PageInfo,
 }
 #[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ReleaseNode {
+    release_assets: ReleaseAssetConnection,
+}
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 struct ReleaseAsset {
     name: String,
+    content_type: String,
+    database_id: u64,
+}
+
+/// The maximum number of repositories to query at once
+///
+/// Every repository can request up to 100 releases with 100 assets each,
+/// and GitHub limits a query to 500,000 nodes.
+const GRAPHQL_BATCH_SIZE: usize = 40;
+
+/// The fields requested for a page of a repository's releases,
+/// using the `$cursor` variable to get the page after it
+const REPOSITORY_FRAGMENT: &str = "
+fragment RepositoryReleases on Repository {
+    owner {
+        login
+    }
+    name
+    releases(first: 100, after: $cursor) {
+        nodes {
+            id
+            name
+            tagName
+            description
+            isPrerelease
+            createdAt
+            publishedAt
+            releaseAssets(first: 100) {
+                ...ReleaseAssets
+            }
+        }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
+    }
+}";
+
+/// The fields requested for a page of a release's assets
+const RELEASE_ASSETS_FRAGMENT: &str = "
+fragment ReleaseAssets on ReleaseAssetConnection {
+    nodes {
+        name
+        contentType
+        databaseId
+    }
+    pageInfo {
+        hasNextPage
+        endCursor
+    }
+}";
+
+/// The query for the releases of `repos` (aliased by their index, e.g. `_0`),
+/// starting from the page after `cursor` if provided
+fn repositories_request(repos: &[(String, String)], cursor: Option<String>) -> GraphQlRequest {
+    let mut variables = HashMap::from([("cursor".to_owned(), cursor)]);
+    let mut definitions = vec!["$cursor: String".to_owned()];
+    let mut fields = String::new();
+    for (i, (owner, name)) in repos.iter().enumerate() {
+        variables.insert(format!("owner{i}"), Some(owner.clone()));
+        variables.insert(format!("name{i}"), Some(name.clone()));
+        definitions.push(format!("$owner{i}: String!, $name{i}: String!"));
+        fields.push_str(&format!(
+            "_{i}: repository(owner: $owner{i}, name: $name{i}) {{ ...RepositoryReleases }}\n"
+        ));
+    }
+    GraphQlRequest {
+        query: format!(
+            "query({}) {{\n{fields}}}{REPOSITORY_FRAGMENT}{RELEASE_ASSETS_FRAGMENT}",
+            definitions.join(", ")
+        ),
+        variables,
+    }
+}
+
+/// The query for the page of assets after `cursor` of the release with the node ID `release_id`
+fn release_assets_request(release_id: String, cursor: String) -> GraphQlRequest {
+    GraphQlRequest {
+        query: format!(
+            "query($id: ID!, $cursor: String) {{
+                _0: node(id: $id) {{
+                    ... on Release {{
+                        releaseAssets(first: 100, after: $cursor) {{
+                            ...ReleaseAssets
+                        }}
+                    }}
+                }}
+            }}{RELEASE_ASSETS_FRAGMENT}"
+        ),
+        variables: HashMap::from([
+            ("id".to_owned(), Some(release_id)),
+            ("cursor".to_owned(), Some(cursor)),
+        ]),
+    }
+}
+
+/// Send a GraphQL query for a single field aliased `_0` to `github` and get its data
+async fn graphql_single<T: DeserializeOwned>(
+    github: &Octocrab,
+    request: GraphQlRequest,
+) -> Result<T> {
+    let response: GraphQlResponse<T> = github.graphql(&request).await?;
+    if let Some(error) = response.errors.into_iter().next() {
+        return Err(error.into());
+    }
+    response
+        .data
+        .unwrap_or_default()
+        .remove("_0")
+        .flatten()
+        .ok_or(Error::DoesNotExist)
+}
+
+/// Get the files of `releases` and the pages of releases after it in the repository `owner/name`,
+/// using the clients of the repository's host `github`
+///
+/// Stops fetching more pages once a file compatible with `filters` is found.
+async fn github_files(
+    context: &Context,
+    github: &GitHubClient,
+    (owner, name): &(String, String),
+    mut releases: ReleaseConnection,
+    filters: &CompiledFilters,
+) -> Result<Vec<Metadata>> {
+    let mut files = Vec::new();
+    loop {
+        let mut page = Vec::new();
+        for mut release in releases.nodes {
+            // Get the rest of the assets of releases with many assets
+            while let (true, Some(cursor)) = (
+                release.release_assets.page_info.has_next_page,
+                &release.release_assets.page_info.end_cursor,
+            ) {
+                let mut more = graphql_single::<ReleaseNode>(
+                    &github.graphql,
+                    release_assets_request(release.id.clone(), cursor.clone()),
+                )
+                .await?
+                .release_assets;
+                release.release_assets.nodes.append(&mut more.nodes);
+                release.release_assets.page_info = more.page_info;
+            }
+            page.extend(release_files(release, &*context.manifest().await));
+        }
+
+        // Replace the compatibility guessed from the filenames with the assets' mod descriptors
+        if context.inspect_github_assets {
+            inspect::inspect_assets(
+                context,
+                &github.rest,
+                (owner, name),
+                page.iter_mut().map(|(metadata, id)| (metadata, *id)),
+                filters,
+            )
+            .await;
+        }
+        files.extend(page.into_iter().map(|(metadata, _)| metadata));
+
+        let compatible = check::select_latest(files.iter(), filters).is_ok();
+        match (compatible, releases.page_info.end_cursor) {
+            (false, Some(cursor)) if releases.page_info.has_next_page => {
+                releases = graphql_single::<ResponseData>(
+                    &github.graphql,
+                    repositories_request(&[(owner.clone(), name.clone())], Some(cursor)),
+                )
+                .await?
+                .releases;
+            }
+            _ => return Ok(files),
+        }
+    }
+}
+
+/// Query the releases of the repositories in `batch` using the clients of their host `github`
+///
+/// Returns the host they referred to, the repository, and the files of the repositories that were found,
+/// and adds the errors of the other repositories to `errors`.
+async fn github_batch(
+    context: &Context,
+    github: &GitHubClient,
+    batch: &[(Option<String>, (String, String))],
+    filters: &CompiledFilters,
+    errors: &mut Vec<(String, Error)>,
+) -> Result<Vec<(Option<String>, (String, String), Vec<Metadata>)>> {
+    let repos = batch.iter().map(|(_, repo)| repo.clone()).collect_vec();
+    let response: GraphQlResponse<ResponseData> = github
+        .graphql
+        .graphql(&repositories_request(&repos, None))
+        .await?;
+    let mut data = response.data.unwrap_or_default();
+
+    // Map the errors back to the repositories using the aliases in their paths
+    let mut repo_errors = HashMap::new();
+    let mut other_errors = Vec::new();
+    for error in response.errors {
+        match error.alias_index() {
+            Some(i) if i < batch.len() => {
+                repo_errors.entry(i).or_insert(error);
+            }
+            _ => other_errors.push(error.message),
+        }
+    }
+
+    let mut found = Vec::new();
+    for (i, (host, (owner, name))) in batch.iter().enumerate() {
+        let display_name = format!("{owner}/{name}");
+        if let Some(d) = data.remove(&format!("_{i}")).flatten() {
+            let id = (d.owner.login, d.name);
+            match github_files(context, github, &id, d.releases, filters).await {
+                Ok(files) => found.push((host.clone(), id, files)),
+                Err(err) => errors.push((display_name, err)),
+            }
+        } else if let Some(error) = repo_errors.remove(&i) {
+            errors.push((display_name, error.into()));
+        } else if !other_errors.is_empty() {
+            errors.push((display_name, Error::GitHubError(other_errors.join(", "))));
+        } else {
+            errors.push((display_name, Error::DoesNotExist));
+        }
+    }
+    Ok(found)
+}
+
+/// Get the metadata and database ID of the assets of `release`
+fn release_files(release: Release, manifest: &Manifest) -> Vec<(Metadata, u64)> {
+    release
+        .release_assets
+        .nodes
+        .into_iter()
+        .map(|asset| {
+            let metadata = Metadata {
+                title: release.name.clone(),
+                description: release.description.clone(),
+                version_number: release.tag_name.clone(),
+                published: release.published_at.unwrap_or(release.created_at),
+                featured: false,
+                environment: None,
+                asset_kind: AssetKind::classify(&asset.name, &asset.content_type),
+                channel: if release.is_prerelease {
+                    ReleaseChannel::Beta
+                } else {
+                    ReleaseChannel::Release
+                },
+                game_versions: game_version::versions_in_filename(&asset.name, manifest),
+                loaders: asset
+                    .name
+                    .trim_end_matches(".jar")
+                    .split(['-', '_', '+'])
+                    .filter_map(|s| ModLoader::from_str(s).ok())
+                    .collect_vec(),
+                filename: asset.name,
+            };
+            (metadata, asset.database_id)
+        })
+        .collect_vec()
 }
 
-pub fn parse_id(id: String) -> ModIdentifier {
+/// Parse `id` into a CurseForge project ID, a GitHub repository (`owner/repo`),
+/// a GitLab project (`gitlab:group/project`), or a Modrinth project ID or slug
+///
+/// GitHub repositories and GitLab projects can be pinned to a release using `owner/repo@tag`,
+/// optionally followed by a regex for the asset's filename (e.g. `owner/repo@v1.2.0:fabric`).
+/// They can refer to a host configured in `context` using `host:owner/repo`, GitLab hosts are checked first.
+pub fn parse_id(context: &Context, id: String) -> ModIdentifier {
     if let Ok(id) = id.parse() {
         ModIdentifier::CurseForgeProject(id)
     } else {
-        let split = id.split('/').collect_vec();
+        // Repository names can't contain `@` or `:`, and tags can't contain `:`
+        let (repo, pin) = match id.split_once('@') {
+            Some((repo, pin)) => (repo, Some(pin)),
+            None => (id.as_str(), None),
+        };
+        let (host, repo) = match repo.split_once(':') {
+            Some((host, repo)) => (Some(host.to_owned()), repo),
+            None => (None, repo),
+        };
+        let pin = pin.map(|pin| match pin.split_once(':') {
+            Some((tag, asset)) => (tag.to_owned(), Some(asset.to_owned())),
+            None => (pin.to_owned(), None),
+        });
+
+        if let Some(host) = host
+            .as_deref()
+            .filter(|host| *host == GITLAB_PREFIX || context.gitlab_hosts.is_host(host))
+        {
+            let project = repo.to_owned();
+            let host = (host != GITLAB_PREFIX).then(|| host.to_owned());
+            return match pin {
+                Some((tag, asset)) => ModIdentifier::PinnedGitLabRelease {
+                    project,
+                    tag,
+                    asset,
+                    host,
+                },
+                None => ModIdentifier::GitLabProject { project, host },
+            };
+        }
+
+        let split = repo.split('/').collect_vec();
         if split.len() == 2 {
-            ModIdentifier::GitHubRepository(split[0].to_owned(), split[1].to_owned())
+            let repo = (split[0].to_owned(), split[1].to_owned());
+            match pin {
+                Some((tag, asset)) => ModIdentifier::PinnedGitHubRelease {
+                    repo,
+                    tag,
+                    asset,
+                    host,
+                },
+                None => match host {
+                    Some(host) => ModIdentifier::GitHubHostRepository { host, repo },
+                    None => ModIdentifier::GitHubRepository(repo.0, repo.1),
+                },
+            }
         } else {
             ModIdentifier::ModrinthProject(id)
         }
@@ -100,8 +489,10 @@ pub fn parse_id(id: String) -> ModIdentifier {
 ///
 /// Classifies the `identifiers` into the appropriate platforms, sends batch requests to get the necessary information,
 /// checks details about the projects, and adds them to `profile` if suitable.
-/// Performs checks on the mods to see whether they're compatible with the profile if `perform_checks` is true
+/// Performs checks on the mods to see whether they're compatible with the profile if `perform_checks` is true,
+/// using the settings from the config in `context`
 pub async fn add(
+    context: &Context,
     profile: &mut Profile,
     identifiers: Vec<ModIdentifier>,
     perform_checks: bool,
@@ -111,13 +502,30 @@ pub async fn add(
     let mut mr_ids = Vec::new();
     let mut cf_ids = Vec::new();
     let mut gh_ids = Vec::new();
+    let mut gh_pins = Vec::new();
+    let mut gl_ids = Vec::new();
+    let mut gl_pins = Vec::new();
     let mut errors = Vec::new();
 
     for id in identifiers {
         match id {
             ModIdentifier::CurseForgeProject(id) => cf_ids.push(id),
             ModIdentifier::ModrinthProject(id) => mr_ids.push(id),
-            ModIdentifier::GitHubRepository(o, r) => gh_ids.push((o, r)),
+            ModIdentifier::GitHubRepository(o, r) => gh_ids.push((None, (o, r))),
+            ModIdentifier::GitHubHostRepository { host, repo } => gh_ids.push((Some(host), repo)),
+            ModIdentifier::PinnedGitHubRelease {
+                repo,
+                tag,
+                asset,
+                host,
+            } => gh_pins.push((repo, tag, asset, host)),
+            ModIdentifier::GitLabProject { project, host } => gl_ids.push((project, host)),
+            ModIdentifier::PinnedGitLabRelease {
+                project,
+                tag,
+                asset,
+                host,
+            } => gl_pins.push((project, tag, asset, host)),
 
             _ => todo!("Adding pinned projects is not supported yet"),
         }
@@ -141,105 +549,40 @@ pub async fn add(
         Vec::new()
     };
 
-    let gh_repos =
-        {
-            // Construct GraphQl query using raw strings
-            let mut graphql_query = "{".to_string();
-            for (i, (owner, name)) in gh_ids.iter().enumerate() {
-                graphql_query.push_str(&format!(
-                    "_{i}: repository(owner: \"{owner}\", name: \"{name}\") {{
-                    owner {{
-                        login
-                    }}
-                    name
-                    releases(first: 100) {{
-                        nodes {{
-                            name
-                            description
-                            isPrerelease
-                            releaseAssets(first: 10) {{
-                                nodes {{
-                                    name
-                                }}
-                            }}
-                        }}
-                    }}
-                }}"
-                ));
-            }
-            graphql_query.push('}');
-
-            // Send the query
-            let response: GraphQlResponse = if !gh_ids.is_empty() {
-                GITHUB_API
-                    .graphql(&HashMap::from([("query", graphql_query)]))
-                    .await?
-            } else {
-                GraphQlResponse {
-                    data: HashMap::new(),
-                    errors: Vec::new(),
-                }
-            };
+    // The filters used to check whether GitHub repositories are compatible,
+    // so that fetching their releases can stop once a compatible file is found
+    let check_filters = CompiledFilters::new(
+        context,
+        compatibility_filters(profile, ContentType::Mod, override_profile, &filters),
+    )
+    .await?;
 
-            errors.extend(response.errors.into_iter().map(|v| {
-                (
-                    {
-                        let id = &gh_ids[v.path[0]
-                            .strip_prefix('_')
-                            .and_then(|s| s.parse::<usize>().ok())
-                            .expect("Unexpected response data")];
-                        format!("{}/{}", id.0, id.1)
-                    },
-                    if v.type_ == "NOT_FOUND" {
-                        Error::DoesNotExist
-                    } else {
-                        Error::GitHubError(v.message)
-                    },
-                )
-            }));
-
-            response
-                .data
-                .into_values()
-                .flatten()
-                .map(|d| {
-                    (
-                        (d.owner.login, d.name),
-                        d.releases
-                            .nodes
-                            .into_iter()
-                            .flat_map(|release| {
-                                release.release_assets.nodes.into_iter().map(move |asset| {
-                                    Metadata {
-                                        title: release.name.clone(),
-                                        description: release.description.clone(),
-                                        channel: if release.is_prerelease {
-                                            ReleaseChannel::Beta
-                                        } else {
-                                            ReleaseChannel::Release
-                                        },
-                                        game_versions: asset
-                                            .name
-                                            .trim_end_matches(".jar")
-                                            .split(['-', '_', '+'])
-                                            .map(|s| s.trim_start_matches("mc"))
-                                            .map(ToOwned::to_owned)
-                                            .collect_vec(),
-                                        loaders: asset
-                                            .name
-                                            .trim_end_matches(".jar")
-                                            .split(['-', '_', '+'])
-                                            .filter_map(|s| ModLoader::from_str(s).ok())
-                                            .collect_vec(),
-                                        filename: asset.name,
-                                    }
-                                })
-                            })
-                            .collect_vec(),
+    // Group the repositories by the GitHub host they're on, since each host has to be queried separately
+    let mut gh_hosts = BTreeMap::<_, (GitHubClient, Vec<_>)>::new();
+    for (host, (owner, name)) in gh_ids {
+        match context.github_hosts.client(host.as_deref(), &owner, &name) {
+            Ok(client) => {
+                gh_hosts
+                    .entry(
+                        context
+                            .github_hosts
+                            .host_for(host.as_deref(), &owner, &name),
                     )
-                })
-                .collect_vec()
-        };
+                    .or_insert_with(|| (client, Vec::new()))
+                    .1
+                    .push((host, (owner, name)));
+            }
+            Err(err) => errors.push((format!("{owner}/{name}"), err.into())),
+        }
+    }
+
+    let mut gh_repos = Vec::new();
+    for (github, ids) in gh_hosts.into_values() {
+        for batch in ids.chunks(GRAPHQL_BATCH_SIZE) {
+            gh_repos
+                .extend(github_batch(context, &github, batch, &check_filters, &mut errors).await?);
+        }
+    }
 
     let mut success_names = Vec::new();
 
@@ -249,6 +592,7 @@ pub async fn add(
         }
 
         match curseforge(
+            context,
             &project,
             profile,
             perform_checks,
@@ -276,6 +620,7 @@ pub async fn add(
         }
 
         match modrinth(
+            context,
             &project,
             profile,
             perform_checks,
@@ -294,9 +639,11 @@ pub async fn add(
             .map(|id| (id.to_string(), Error::DoesNotExist)),
     );
 
-    for (repo, asset_names) in gh_repos {
+    for (host, repo, asset_names) in gh_repos {
         match github(
+            context,
             &repo,
+            host,
             profile,
             Some(asset_names),
             override_profile,
@@ -309,15 +656,114 @@ pub async fn add(
         }
     }
 
+    for (repo, tag, asset, host) in gh_pins {
+        let name = format!("{}/{}@{tag}", repo.0, repo.1);
+        match github_release(
+            context,
+            repo,
+            tag,
+            asset,
+            host,
+            profile,
+            perform_checks,
+            override_profile,
+            filters.clone(),
+        )
+        .await
+        {
+            Ok(()) => success_names.push(name),
+            Err(err) => errors.push((name, err)),
+        }
+    }
+
+    for (project, host) in gl_ids {
+        match gitlab(
+            context,
+            project.clone(),
+            host,
+            profile,
+            perform_checks,
+            override_profile,
+            filters.clone(),
+        )
+        .await
+        {
+            Ok(()) => success_names.push(project),
+            Err(err) => errors.push((project, err)),
+        }
+    }
+
+    for (project, tag, asset, host) in gl_pins {
+        let name = format!("{project}@{tag}");
+        match gitlab_release(
+            context,
+            project,
+            tag,
+            asset,
+            host,
+            profile,
+            perform_checks,
+            override_profile,
+            filters.clone(),
+        )
+        .await
+        {
+            Ok(()) => success_names.push(name),
+            Err(err) => errors.push((name, err)),
+        }
+    }
+
     Ok((success_names, errors))
 }
 
+/// Get the filters that a project's files are checked with when adding it to `profile`,
+/// which are the game version and loader filters that apply to `content_type`
+///
+/// These are the profile's filters followed by the project's `filters`, or only the profile's filters
+/// if `override_profile` is true.
+fn compatibility_filters(
+    profile: &Profile,
+    content_type: ContentType,
+    override_profile: bool,
+    filters: &[Filter],
+) -> Vec<Filter> {
+    let mut check_filters = content_type.applicable_filters(profile.filters.clone());
+    if !override_profile {
+        check_filters.extend_from_slice(filters);
+    }
+    check_filters.retain(|f| {
+        matches!(
+            f,
+            Filter::GameVersionStrict(_)
+                | Filter::GameVersionMinor(_)
+                | Filter::GameVersionRange(_)
+                | Filter::GameVersionDevelopment(_)
+                | Filter::ModLoaderAny(_)
+                | Filter::ModLoaderPrefer(_)
+        )
+    });
+    check_filters
+}
+
+/// Whether `identifier` is the GitHub repository `owner/repo`, pinned or not
+fn is_github_repo(identifier: &ModIdentifier, owner: &str, repo: &str) -> bool {
+    match identifier {
+        ModIdentifier::GitHubRepository(o, r)
+        | ModIdentifier::GitHubHostRepository { repo: (o, r), .. }
+        | ModIdentifier::PinnedGitHubRepository((o, r), _)
+        | ModIdentifier::PinnedGitHubRelease { repo: (o, r), .. } => o == owner && r == repo,
+        _ => false,
+    }
+}
+
 /// Check if the repo of `repo_handler` exists, releases mods, and is compatible with `profile`.
-/// If so, add it to the `profile`.
+/// If so, add it to the `profile`, referring to the configured GitHub host named `host` if provided.
 ///
 /// Returns the name of the repository to display to the user
 pub async fn github(
+    context: &Context,
     id: &(impl AsRef<str> + ToString, impl AsRef<str> + ToString),
+    host: Option<String>,
     profile: &mut Profile,
     perform_checks: Option<Vec<Metadata>>,
     override_profile: bool,
@@ -326,10 +772,7 @@ pub async fn github(
     // Check if project has already been added
     if profile.mods.iter().any(|mod_| {
         mod_.name.eq_ignore_ascii_case(id.1.as_ref())
-            || matches!(
-                &mod_.identifier,
-                ModIdentifier::GitHubRepository(owner, repo) if owner == id.0.as_ref() && repo == id.1.as_ref(),
-            )
+            || is_github_repo(&mod_.identifier, id.0.as_ref(), id.1.as_ref())
     }) {
         return Err(Error::AlreadyAdded);
     }
@@ -338,20 +781,306 @@ pub async fn github(
         // Check if the repo is compatible
         check::select_latest(
             download_files.iter(),
-            if override_profile {
-                profile.filters.clone()
-            } else {
-                [profile.filters.clone(), filters.clone()].concat()
+            &CompiledFilters::new(
+                context,
+                compatibility_filters(profile, ContentType::Mod, override_profile, &filters),
+            )
+            .await?,
+        )?;
+    }
+
+    // Add it to the profile
+    profile.push_mod(
+        id.1.as_ref().trim().to_string(),
+        match host {
+            Some(host) => ModIdentifier::GitHubHostRepository {
+                host,
+                repo: (id.0.to_string(), id.1.to_string()),
             },
+            None => ModIdentifier::GitHubRepository(id.0.to_string(), id.1.to_string()),
+        },
+        id.1.as_ref().trim().to_string(),
+        ContentType::Mod,
+        None,
+        override_profile,
+        filters,
+    );
+
+    Ok(())
+}
+
+/// The name of a project pinned to the release with `tag`, and the asset matching the `asset` regex if provided,
+/// so that a project can be pinned to several releases or assets
+fn pinned_name(name: &str, tag: &str, asset: Option<&str>) -> String {
+    match asset {
+        Some(asset) => format!("{name}@{tag}:{asset}"),
+        None => format!("{name}@{tag}"),
+    }
+}
+
+/// Check if the release with `tag` in the repository `repo` exists and has an asset matching the `asset` regex,
+/// or an asset compatible with `profile` if `asset` isn't provided and `perform_checks` is true.
+/// If so, add the repository to the `profile` pinned to that release.
+///
+/// A repository can be pinned to several releases or assets, so the mod is named `repo@tag` or `repo@tag:asset`.
+/// The repository is on the configured GitHub host named `host` if provided.
+#[allow(clippy::too_many_arguments)]
+pub async fn github_release(
+    context: &Context,
+    repo: (String, String),
+    tag: String,
+    asset: Option<String>,
+    host: Option<String>,
+    profile: &mut Profile,
+    perform_checks: bool,
+    override_profile: bool,
+    filters: Vec<Filter>,
+) -> Result<()> {
+    // Report invalid asset regexes now instead of when upgrading
+    if let Some(pattern) = &asset {
+        Regex::new(pattern)?;
+    }
+
+    // Check if the repository has already been added, or pinned to the same release and asset
+    let name = pinned_name(repo.1.trim(), &tag, asset.as_deref());
+    if profile.mods.iter().any(|mod_| {
+        mod_.name.eq_ignore_ascii_case(&name)
+            || match &mod_.identifier {
+                ModIdentifier::GitHubRepository(o, r)
+                | ModIdentifier::GitHubHostRepository { repo: (o, r), .. } => {
+                    o == &repo.0 && r == &repo.1
+                }
+                ModIdentifier::PinnedGitHubRelease {
+                    repo: r,
+                    tag: t,
+                    asset: a,
+                    ..
+                } => r == &repo && t == &tag && a == &asset,
+                _ => false,
+            }
+    }) {
+        return Err(Error::AlreadyAdded);
+    }
+
+    // Check if the release has the pinned asset, or a compatible one
+    let check_filters = match &asset {
+        Some(pattern) => Some(vec![Filter::Filename(pattern.clone())]),
+        None if perform_checks => Some(compatibility_filters(
+            profile,
+            ContentType::Mod,
+            override_profile,
+            &filters,
+        )),
+        None => None,
+    };
+    let check_filters = match check_filters {
+        Some(check_filters) => Some(CompiledFilters::new(context, check_filters).await?),
+        None => None,
+    };
+
+    let github = context
+        .github_hosts
+        .client(host.as_deref(), &repo.0, &repo.1)?
+        .rest;
+    let release = github
+        .repos(&repo.0, &repo.1)
+        .releases()
+        .get_by_tag(&tag)
+        .await?;
+    let files = from_gh_releases_inspected(
+        context,
+        &github,
+        (&repo.0, &repo.1),
+        vec![release],
+        check_filters
+            .as_ref()
+            .unwrap_or(&CompiledFilters::default()),
+    )
+    .await;
+    if let Some(check_filters) = check_filters {
+        check::select_latest(files.iter().map(|(metadata, _)| metadata), &check_filters)?;
+    }
+
+    // Add it to the profile
+    profile.push_mod(
+        name,
+        ModIdentifier::PinnedGitHubRelease {
+            repo: repo.clone(),
+            tag,
+            asset,
+            host,
+        },
+        repo.1.trim().to_string(),
+        ContentType::Mod,
+        None,
+        override_profile,
+        filters,
+    );
+
+    Ok(())
+}
+
+/// Whether `identifier` is the GitLab project `project`, pinned or not
+fn is_gitlab_project(identifier: &ModIdentifier, project: &str) -> bool {
+    match identifier {
+        ModIdentifier::GitLabProject { project: p, .. }
+        | ModIdentifier::PinnedGitLabRelease { project: p, .. } => p == project,
+        _ => false,
+    }
+}
+
+/// The name of the GitLab project `project`, which is the last part of its path
+fn gitlab_project_name(project: &str) -> String {
+    project
+        .rsplit('/')
+        .next()
+        .unwrap_or(project)
+        .trim()
+        .to_string()
+}
+
+/// Check if the GitLab project `project` exists, releases mods, and is compatible with `profile`
+/// if `perform_checks` is true. If so, add it to the `profile`.
+///
+/// The project is on the configured GitLab host named `host` if provided, otherwise gitlab.com.
+pub async fn gitlab(
+    context: &Context,
+    project: String,
+    host: Option<String>,
+    profile: &mut Profile,
+    perform_checks: bool,
+    override_profile: bool,
+    filters: Vec<Filter>,
+) -> Result<()> {
+    let name = gitlab_project_name(&project);
+
+    // Check if project has already been added
+    if profile.mods.iter().any(|mod_| {
+        mod_.name.eq_ignore_ascii_case(&name) || is_gitlab_project(&mod_.identifier, &project)
+    }) {
+        return Err(Error::AlreadyAdded);
+    }
+
+    let gitlab = context.gitlab_hosts.client(host.as_deref())?;
+    if perform_checks {
+        // Check if the project is compatible, fetching more releases until a compatible file is found
+        let check_filters = CompiledFilters::new(
+            context,
+            compatibility_filters(profile, ContentType::Mod, override_profile, &filters),
         )
         .await?;
+        let mut files = Vec::new();
+        let mut page = 1;
+        loop {
+            let (releases, next_page) = gitlab.list_releases(&project, page).await?;
+            files.extend(from_gl_releases(releases, &*context.manifest().await));
+            match (
+                check::select_latest(files.iter().map(|(metadata, _)| metadata), &check_filters),
+                next_page,
+            ) {
+                (Ok(_), _) => break,
+                (Err(_), Some(next_page)) => page = next_page,
+                (Err(err), None) => return Err(err.into()),
+            }
+        }
+    } else {
+        // Check if the project exists
+        gitlab.list_releases(&project, 1).await?;
     }
 
     // Add it to the profile
     profile.push_mod(
-        id.1.as_ref().trim().to_string(),
-        ModIdentifier::GitHubRepository(id.0.to_string(), id.1.to_string()),
-        id.1.as_ref().trim().to_string(),
+        name.clone(),
+        ModIdentifier::GitLabProject { project, host },
+        name,
+        ContentType::Mod,
+        None,
+        override_profile,
+        filters,
+    );
+
+    Ok(())
+}
+
+/// Check if the release with `tag` in the GitLab project `project` exists and has an asset matching the `asset` regex,
+/// or an asset compatible with `profile` if `asset` isn't provided and `perform_checks` is true.
+/// If so, add the project to the `profile` pinned to that release.
+///
+/// A project can be pinned to several releases or assets, so the mod is named `name@tag` or `name@tag:asset`.
+/// The project is on the configured GitLab host named `host` if provided, otherwise gitlab.com.
+#[allow(clippy::too_many_arguments)]
+pub async fn gitlab_release(
+    context: &Context,
+    project: String,
+    tag: String,
+    asset: Option<String>,
+    host: Option<String>,
+    profile: &mut Profile,
+    perform_checks: bool,
+    override_profile: bool,
+    filters: Vec<Filter>,
+) -> Result<()> {
+    // Report invalid asset regexes now instead of when upgrading
+    if let Some(pattern) = &asset {
+        Regex::new(pattern)?;
+    }
+
+    // Check if the project has already been added, or pinned to the same release and asset
+    let project_name = gitlab_project_name(&project);
+    let name = pinned_name(&project_name, &tag, asset.as_deref());
+    if profile.mods.iter().any(|mod_| {
+        mod_.name.eq_ignore_ascii_case(&name)
+            || match &mod_.identifier {
+                ModIdentifier::GitLabProject { project: p, .. } => p == &project,
+                ModIdentifier::PinnedGitLabRelease {
+                    project: p,
+                    tag: t,
+                    asset: a,
+                    ..
+                } => p == &project && t == &tag && a == &asset,
+                _ => false,
+            }
+    }) {
+        return Err(Error::AlreadyAdded);
+    }
+
+    let release = context
+        .gitlab_hosts
+        .client(host.as_deref())?
+        .get_release(&project, &tag)
+        .await?;
+    let files = from_gl_releases([release], &*context.manifest().await);
+
+    // Check if the release has the pinned asset, or a compatible one
+    let check_filters = match &asset {
+        Some(pattern) => Some(vec![Filter::Filename(pattern.clone())]),
+        None if perform_checks => Some(compatibility_filters(
+            profile,
+            ContentType::Mod,
+            override_profile,
+            &filters,
+        )),
+        None => None,
+    };
+    if let Some(check_filters) = check_filters {
+        check::select_latest(
+            files.iter().map(|(metadata, _)| metadata),
+            &CompiledFilters::new(context, check_filters).await?,
+        )?;
+    }
+
+    // Add it to the profile
+    profile.push_mod(
+        name,
+        ModIdentifier::PinnedGitLabRelease {
+            project,
+            tag,
+            asset,
+            host,
+        },
+        project_name,
+        ContentType::Mod,
+        None,
         override_profile,
         filters,
     );
@@ -361,15 +1090,57 @@ pub async fn github(
 
 use ferinth::structures::project::{Project, ProjectType};
 
-/// Check if the project of `project_id` has not already been added, is a mod, and is compatible with `profile`.
+/// Get the type of content that the Modrinth `project` provides, or `None` if it isn't supported
+fn modrinth_content_type(project: &Project, profile: &Profile) -> Option<ContentType> {
+    let loaders = project
+        .loaders
+        .iter()
+        .map(|s| ModLoader::from(s.as_str()))
+        .collect_vec();
+    let supports_plugins = loaders.iter().any(ModLoader::is_plugin_loader);
+    let supports_mods = loaders
+        .iter()
+        .any(|l| !l.is_plugin_loader() && !matches!(l, ModLoader::Other(_)));
+
+    Some(match project.project_type {
+        // Data packs are often listed as mods with a `datapack` loader
+        ProjectType::Mod
+            if !project.loaders.is_empty() && project.loaders.iter().all(|l| l == "datapack") =>
+        {
+            ContentType::DataPack
+        }
+        // Plugins are often listed as mods, and some projects provide both mods and plugins
+        ProjectType::Mod
+            if supports_plugins
+                && (!supports_mods
+                    || profile
+                        .filters
+                        .mod_loader()
+                        .is_some_and(ModLoader::is_plugin_loader)) =>
+        {
+            ContentType::Plugin
+        }
+        ProjectType::Mod => ContentType::Mod,
+        ProjectType::Plugin => ContentType::Plugin,
+        ProjectType::ResourcePack => ContentType::ResourcePack,
+        ProjectType::Shader => ContentType::Shader,
+        ProjectType::Datapack => ContentType::DataPack,
+        _ => return None,
+    })
+}
+
+/// Check if the project of `project_id` has not already been added, is a supported type of content, and is compatible with `profile`.
 /// If so, add it to the `profile`.
 pub async fn modrinth(
+    context: &Context,
     project: &Project,
     profile: &mut Profile,
     perform_checks: bool,
     override_profile: bool,
     filters: Vec<Filter>,
 ) -> Result<()> {
+    let content_type = modrinth_content_type(project, profile);
+
     // Check if project has already been added
     if profile.mods.iter().any(|mod_| {
         mod_.name.eq_ignore_ascii_case(&project.title)
@@ -380,68 +1151,91 @@ pub async fn modrinth(
     }) {
         Err(Error::AlreadyAdded)
 
-    // Check if the project is a mod
-    } else if project.project_type != ProjectType::Mod {
-        Err(Error::NotAMod)
+    // Check if the profile has a world to install data packs to
+    } else if content_type == Some(ContentType::DataPack) && profile.world.is_none() {
+        Err(Error::NoWorld)
 
-    // Check if the project is compatible
-    } else {
+    // Check if the project's content is supported
+    } else if let Some(content_type) = content_type {
+        let environment = Environment::from_support(&project.client_side, &project.server_side);
+
+        // Check if the project is compatible
         if perform_checks {
             check::select_latest(
                 [Metadata {
                     filename: "".to_owned(),
                     title: "".to_owned(),
                     description: "".to_owned(),
+                    version_number: "".to_owned(),
+                    featured: false,
+                    // Only the game version and loader filters are used
+                    published: DateTime::UNIX_EPOCH,
+                    environment,
+                    asset_kind: AssetKind::Primary,
                     game_versions: project.game_versions.clone(),
                     loaders: project
                         .loaders
                         .iter()
-                        .filter_map(|s| ModLoader::from_str(s).ok())
+                        .map(|s| ModLoader::from(s.as_str()))
                         .collect_vec(),
                     channel: ReleaseChannel::Release,
                 }]
                 .iter(),
-                if override_profile {
-                    profile.filters.clone()
-                } else {
-                    [profile.filters.clone(), filters.clone()].concat()
-                }
-                .iter()
-                .filter(|f| {
-                    matches!(
-                        f,
-                        Filter::GameVersionStrict(_)
-                            | Filter::GameVersionMinor(_)
-                            | Filter::ModLoaderAny(_)
-                            | Filter::ModLoaderPrefer(_)
-                    )
-                })
-                .cloned()
-                .collect_vec(),
-            )
-            .await?;
+                &CompiledFilters::new(
+                    context,
+                    compatibility_filters(profile, content_type, override_profile, &filters),
+                )
+                .await?,
+            )?;
         }
         // Add it to the profile
         profile.push_mod(
             project.title.trim().to_owned(),
             ModIdentifier::ModrinthProject(project.id.clone()),
             project.slug.to_owned(),
+            content_type,
+            environment,
             override_profile,
             filters,
         );
         Ok(())
+    } else {
+        Err(Error::NotAMod)
     }
 }
 
-/// Check if the mod of `project_id` has not already been added, is a mod, and is compatible with `profile`.
+use furse::structures::common_structs::ModLoaderType;
+
+/// Get the type of content that the CurseForge `project` provides using the class in its URL,
+/// or `None` if it isn't supported
+fn curseforge_content_type(project: &furse::structures::mod_structs::Mod) -> Option<ContentType> {
+    match project
+        .links
+        .website_url
+        .path_segments()
+        .and_then(|mut s| s.nth(1))
+    {
+        Some("mc-mods") => Some(ContentType::Mod),
+        Some("texture-packs") => Some(ContentType::ResourcePack),
+        Some("shaders") => Some(ContentType::Shader),
+        Some("data-packs") => Some(ContentType::DataPack),
+        Some("bukkit-plugins") => Some(ContentType::Plugin),
+        _ => None,
+    }
+}
+
+/// Check if the mod of `project_id` has not already been added, is a supported type of content, and is compatible with `profile`.
 /// If so, add it to the `profile`.
 pub async fn curseforge(
+    context: &Context,
     project: &furse::structures::mod_structs::Mod,
     profile: &mut Profile,
     perform_checks: bool,
     override_profile: bool,
     filters: Vec<Filter>,
 ) -> Result<()> {
+    let content_type = curseforge_content_type(project);
+
     // Check if project has already been added
     if profile.mods.iter().any(|mod_| {
         mod_.name.eq_ignore_ascii_case(&project.name)
@@ -453,18 +1247,27 @@ pub async fn curseforge(
     } else if Some(false) == project.allow_mod_distribution {
         Err(Error::DistributionDenied)
 
-    // Check if the project is a Minecraft mod
-    } else if !project.links.website_url.as_str().contains("mc-mods") {
-        Err(Error::NotAMod)
+    // Check if the profile has a world to install data packs to
+    } else if content_type == Some(ContentType::DataPack) && profile.world.is_none() {
+        Err(Error::NoWorld)
 
-    // Check if the mod is compatible
-    } else {
+    // Check if the project's content is supported
+    } else if let Some(content_type) = content_type {
+        let environment = project.latest_files.first().and_then(cf_file_environment);
+
+        // Check if the mod is compatible
         if perform_checks {
             check::select_latest(
                 [Metadata {
                     filename: "".to_owned(),
                     title: "".to_owned(),
                     description: "".to_owned(),
+                    version_number: "".to_owned(),
+                    featured: false,
+                    // Only the game version and loader filters are used
+                    published: DateTime::UNIX_EPOCH,
+                    environment,
+                    asset_kind: AssetKind::Primary,
                     game_versions: project
                         .latest_files_indexes
                         .iter()
@@ -476,40 +1279,32 @@ pub async fn curseforge(
                         .filter_map(|i| {
                             i.mod_loader
                                 .as_ref()
-                                .and_then(|l| ModLoader::from_str(&format!("{:?}", l)).ok())
+                                .filter(|l| !matches!(l, ModLoaderType::Any))
+                                .map(|l| ModLoader::from(format!("{:?}", l)))
                         })
                         .collect_vec(),
                     channel: ReleaseChannel::Release,
                 }]
                 .iter(),
-                if override_profile {
-                    profile.filters.clone()
-                } else {
-                    [profile.filters.clone(), filters.clone()].concat()
-                }
-                .iter()
-                .filter(|f| {
-                    matches!(
-                        f,
-                        Filter::GameVersionStrict(_)
-                            | Filter::GameVersionMinor(_)
-                            | Filter::ModLoaderAny(_)
-                            | Filter::ModLoaderPrefer(_)
-                    )
-                })
-                .cloned()
-                .collect_vec(),
-            )
-            .await?;
+                &CompiledFilters::new(
+                    context,
+                    compatibility_filters(profile, content_type, override_profile, &filters),
+                )
+                .await?,
+            )?;
         }
         profile.push_mod(
             project.name.trim().to_string(),
             ModIdentifier::CurseForgeProject(project.id),
             project.slug.clone(),
+            content_type,
+            environment,
             override_profile,
             filters,
         );
 
         Ok(())
+    } else {
+        Err(Error::NotAMod)
     }
 }
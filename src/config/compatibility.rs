@@ -0,0 +1,108 @@
+use super::structs::ModLoader;
+use serde::{Deserialize, Serialize};
+
+/// A rule stating that `loader` can run files made for the `runs` loader
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LoaderCompatibility {
+    pub loader: ModLoader,
+    pub runs: ModLoader,
+
+    /// The game versions this rule is limited to, the rule applies to all game versions if this is empty
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub game_versions: Vec<String>,
+}
+
+impl LoaderCompatibility {
+    pub fn new(loader: ModLoader, runs: ModLoader) -> Self {
+        Self {
+            loader,
+            runs,
+            game_versions: vec![],
+        }
+    }
+
+    pub fn with_game_versions(mut self, game_versions: Vec<String>) -> Self {
+        self.game_versions = game_versions;
+        self
+    }
+}
+
+/// The loader compatibility rules that libium ships with
+///
+/// Compatibility that requires installing a mod (e.g. Sinytra Connector running Fabric mods on NeoForge)
+/// is not included, and should be added in the config.
+pub fn default_rules() -> Vec<LoaderCompatibility> {
+    use ModLoader::*;
+    vec![
+        LoaderCompatibility::new(Quilt, Fabric),
+        LoaderCompatibility::new(NeoForge, Forge).with_game_versions(vec!["1.20.1".to_owned()]),
+        LoaderCompatibility::new(Purpur, Paper),
+        LoaderCompatibility::new(Paper, Spigot),
+        LoaderCompatibility::new(Spigot, Bukkit),
+        LoaderCompatibility::new(Waterfall, BungeeCord),
+    ]
+}
+
+/// Returns whether a rule limited to `scope` applies to files for `game_versions`
+pub fn scope_applies(scope: &[String], game_versions: &[String]) -> bool {
+    scope.is_empty() || game_versions.iter().any(|v| scope.contains(v))
+}
+
+/// Get the loaders whose files can run on any of `loaders` in order of preference,
+/// along with the game versions that each one is limited to
+///
+/// The provided `loaders` come first, followed by the loaders they can (transitively) run using the `rules`
+/// in breadth-first order.
+pub fn expand(
+    rules: &[LoaderCompatibility],
+    loaders: &[ModLoader],
+) -> Vec<(ModLoader, Vec<String>)> {
+    let mut expanded = loaders
+        .iter()
+        .map(|l| (l.clone(), vec![]))
+        .collect::<Vec<_>>();
+
+    let mut i = 0;
+    while let Some((loader, scope)) = expanded.get(i).cloned() {
+        for rule in rules.iter().filter(|rule| rule.loader == loader) {
+            if expanded.iter().all(|(l, _)| l != &rule.runs) {
+                let scope = if rule.game_versions.is_empty() {
+                    scope.clone()
+                } else if scope.is_empty() {
+                    rule.game_versions.clone()
+                } else {
+                    let intersection = scope
+                        .iter()
+                        .filter(|v| rule.game_versions.contains(v))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    // The chain of rules doesn't apply to any game version
+                    if intersection.is_empty() {
+                        continue;
+                    }
+                    intersection
+                };
+                expanded.push((rule.runs.clone(), scope));
+            }
+        }
+        i += 1;
+    }
+
+    expanded
+}
+
+/// Get the loaders whose files can run on `loader` for `game_versions` using the `rules`, in order of preference
+///
+/// The first element is always `loader`.
+pub fn compatible_loaders(
+    rules: &[LoaderCompatibility],
+    loader: &ModLoader,
+    game_versions: &[String],
+) -> Vec<ModLoader> {
+    expand(rules, std::slice::from_ref(loader))
+        .into_iter()
+        .filter(|(_, scope)| scope_applies(scope, game_versions))
+        .map(|(l, _)| l)
+        .collect()
+}
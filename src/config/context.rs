@@ -0,0 +1,91 @@
+use super::{
+    compatibility::{self, LoaderCompatibility},
+    github_hosts::GitHubHosts,
+    gitlab_hosts::GitLabHosts,
+    structs::Config,
+    version_groups::VersionGroups,
+};
+use crate::{
+    game_version::{self, Manifest},
+    upgrade::inspect::InspectedAssets,
+};
+use std::{
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+};
+
+/// The settings from a config that are used when adding and upgrading mods
+///
+/// Build this from the config with [`Context::new`], or use [`Context::default`] for the default settings.
+#[derive(Debug, Clone)]
+pub struct Context {
+    /// The loader compatibility rules used when filtering
+    pub loader_compatibility: Vec<LoaderCompatibility>,
+
+    /// The custom game version groups used by the `GameVersionMinor` filter
+    pub version_groups: VersionGroups,
+
+    /// Whether GitHub release assets are downloaded and inspected to determine their compatibility
+    pub inspect_github_assets: bool,
+
+    /// The directory to cache the game versions and inspected GitHub release assets in,
+    /// they're only kept in memory if this isn't set
+    pub cache_dir: Option<PathBuf>,
+
+    /// The list of game versions once it has been loaded, which is shared by clones of this context
+    manifest: Arc<OnceLock<Arc<Manifest>>>,
+
+    /// The GitHub release assets that have been inspected, which are shared by clones of this context
+    pub inspected_assets: Arc<InspectedAssets>,
+
+    /// The configured GitHub hosts, whose clients are shared by clones of this context
+    pub github_hosts: Arc<GitHubHosts>,
+
+    /// The configured GitLab hosts, whose clients are shared by clones of this context
+    pub gitlab_hosts: Arc<GitLabHosts>,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self {
+            loader_compatibility: compatibility::default_rules(),
+            version_groups: VersionGroups::default(),
+            inspect_github_assets: false,
+            cache_dir: None,
+            manifest: Arc::default(),
+            inspected_assets: Arc::default(),
+            github_hosts: Arc::default(),
+            gitlab_hosts: Arc::default(),
+        }
+    }
+}
+
+impl Context {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            loader_compatibility: config.loader_compatibility_rules(),
+            version_groups: config.version_groups.clone(),
+            inspect_github_assets: config.inspect_github_assets,
+            cache_dir: None,
+            manifest: Arc::default(),
+            inspected_assets: Arc::default(),
+            github_hosts: Arc::new(GitHubHosts::new(config.github_hosts.clone())),
+            gitlab_hosts: Arc::new(GitLabHosts::new(config.gitlab_hosts.clone())),
+        }
+    }
+
+    /// Cache data in `cache_dir`, such as the config's directory joined with `.cache`
+    pub fn with_cache_dir(mut self, cache_dir: PathBuf) -> Self {
+        self.cache_dir = Some(cache_dir);
+        self
+    }
+
+    /// Get the list of game versions, [loading](game_version::load_manifest) it from the `cache_dir` the first time
+    pub async fn manifest(&self) -> Arc<Manifest> {
+        if let Some(manifest) = self.manifest.get() {
+            return manifest.clone();
+        }
+        let manifest = Arc::new(game_version::load_manifest(self.cache_dir.as_deref()).await);
+        self.manifest.get_or_init(|| manifest).clone()
+    }
+}
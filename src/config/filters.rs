@@ -1,33 +1,64 @@
-use super::structs::ModLoader;
+use super::structs::{Environment, ModLoader};
 use crate::iter_ext::IterExt as _;
+use chrono::{DateTime, Utc};
 use derive_more::derive::Display;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug, Display, Clone)]
 pub enum Filter {
-    /// Prefers files in the order of the given loaders
+    /// Prefers files in the order of the given loaders, followed by the loaders they are compatible with
     ///
     /// Implementation detail: This filter only works as intended if it is run last on an already filtered list.
     #[display("Mod Loader ({})", _0.iter().display(", "))]
     ModLoaderPrefer(Vec<ModLoader>),
 
-    /// Selects files that are compatible with any of the given loaders
+    /// Selects files that are compatible with any of the given loaders, or the loaders they are compatible with
     #[display("Mod Loader Either ({})", _0.iter().display(", "))]
     ModLoaderAny(Vec<ModLoader>),
 
     /// Selects files strictly compatible with the versions specified
+    ///
+    /// The game version filters also accept symbolic versions (`latest-release`, `latest-snapshot`,
+    /// or `latest-in-<version>`), which are resolved using the game version list when filtering.
     #[display("Game Version ({})", _0.iter().display(", "))]
     GameVersionStrict(Vec<String>),
 
     /// Selects files compatible with the versions specified and related versions that are
     /// considered to not have breaking changes (determined using Modrinth's game version tag list)
+    ///
+    /// Snapshots, pre-releases, and release candidates are grouped with the release they lead up to.
     #[display("Game Version Minor ({})", _0.iter().display(", "))]
     GameVersionMinor(Vec<String>),
 
+    /// Selects files compatible with game versions in the range specified (e.g. `>=1.20.2, <=1.20.6`)
+    ///
+    /// Game versions are ordered chronologically using the game version manifest.
+    #[display("Game Version Range ({_0})")]
+    GameVersionRange(String),
+
+    /// Selects files compatible with the releases specified,
+    /// or the snapshots, pre-releases, and release candidates leading up to them
+    #[display("Game Version Development ({})", _0.iter().display(", "))]
+    GameVersionDevelopment(Vec<String>),
+
     /// Selects files matching the channel provided or more stable channels
     #[display("Release Channel ({_0})")]
     ReleaseChannel(ReleaseChannel),
 
+    /// Selects files that were published at least the provided number of days ago
+    ///
+    /// This can be used to quarantine new releases, which are sometimes broken or even malicious.
+    #[display("Minimum Age ({_0} days)")]
+    MinimumAge(u32),
+
+    /// Selects files published before the provided date
+    #[display("Released Before ({_0})")]
+    ReleasedBefore(DateTime<Utc>),
+
+    /// Selects files published after the provided date
+    #[display("Released After ({_0})")]
+    ReleasedAfter(DateTime<Utc>),
+
     /// Selects the files with filenames matching the provided regex
     #[display("Filename ({_0})")]
     Filename(String),
@@ -39,14 +70,116 @@ pub enum Filter {
     /// Selects files with descriptions matching the provided regex
     #[display("Description ({_0})")]
     Description(String),
+
+    /// Selects files whose mod version number satisfies the provided requirement (e.g. `^0.5` or `>=1.2, <2.0`)
+    ///
+    /// Version numbers are parsed leniently, files whose version number can't be parsed are not selected.
+    #[display("Mod Version ({_0})")]
+    ModVersion(String),
+
+    /// Selects files made for exactly the provided environment,
+    /// e.g. `Environment(Client)` selects client-only files but not files that run on both sides
+    ///
+    /// Use `AnyOf` with `Environment(Both)` to also select files that run on both sides.
+    /// Files with an unknown environment are always selected.
+    #[display("Environment ({_0})")]
+    Environment(Environment),
+
+    /// Selects files that are not selected by the provided filter
+    #[display("Not ({_0})")]
+    Not(Box<Filter>),
+
+    /// Selects files that are selected by any of the provided filters
+    #[display("Any Of ({})", _0.iter().display(", "))]
+    AnyOf(Vec<Filter>),
+
+    /// Selects files that are selected by all of the provided filters
+    ///
+    /// Preference filters (e.g. `ModLoaderPrefer`) are run last on the files selected by the other filters.
+    #[display("All Of ({})", _0.iter().display(", "))]
+    AllOf(Vec<Filter>),
+
+    /// A soft preference for files selected by the provided filter, with the provided weight
+    ///
+    /// Files are not eliminated by this filter. Instead, the files remaining after the other filters are scored
+    /// by adding the weights of the weighted filters that select them, and the highest scoring file is chosen.
+    /// Ties are broken by choosing the most recently published file.
+    ///
+    /// This only has an effect in a profile's or mod's list of filters,
+    /// inside other filters it acts like the filter it contains.
+    #[display("Weighted ({_0}, {_1})")]
+    Weighted(Box<Filter>, u32),
+}
+
+impl Filter {
+    /// Whether this filter, or a filter it contains, is `ModLoaderPrefer`
+    ///
+    /// Such filters only work as intended if they are run last on an already filtered list.
+    pub fn is_preference(&self) -> bool {
+        match self {
+            Filter::ModLoaderPrefer(_) => true,
+            Filter::Not(filter) | Filter::Weighted(filter, _) => filter.is_preference(),
+            Filter::AnyOf(filters) | Filter::AllOf(filters) => {
+                filters.iter().any(Filter::is_preference)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this filter, or a filter it contains, filters by mod loader
+    pub fn uses_loaders(&self) -> bool {
+        match self {
+            Filter::ModLoaderPrefer(_) | Filter::ModLoaderAny(_) => true,
+            Filter::Not(filter) | Filter::Weighted(filter, _) => filter.uses_loaders(),
+            Filter::AnyOf(filters) | Filter::AllOf(filters) => {
+                filters.iter().any(Filter::uses_loaders)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this filter, or a filter it contains, uses the loaders, game versions, or environment of files,
+    /// which can change when GitHub release assets are inspected
+    pub fn uses_compatibility(&self) -> bool {
+        match self {
+            Filter::ModLoaderPrefer(_)
+            | Filter::ModLoaderAny(_)
+            | Filter::GameVersionStrict(_)
+            | Filter::GameVersionMinor(_)
+            | Filter::GameVersionRange(_)
+            | Filter::GameVersionDevelopment(_)
+            | Filter::ModVersion(_)
+            | Filter::Environment(_) => true,
+            Filter::Not(filter) | Filter::Weighted(filter, _) => filter.uses_compatibility(),
+            Filter::AnyOf(filters) | Filter::AllOf(filters) => {
+                filters.iter().any(Filter::uses_compatibility)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this filter, or a filter it contains, is a positive `Filename` filter
+    ///
+    /// Auxiliary GitHub release assets (e.g. sources JARs) are only selected if such a filter is used.
+    /// `Filename` filters inside `Not` only exclude files, so they don't count.
+    pub fn selects_filename(&self) -> bool {
+        match self {
+            Filter::Filename(_) => true,
+            Filter::Weighted(filter, _) => filter.selects_filename(),
+            Filter::AnyOf(filters) | Filter::AllOf(filters) => {
+                filters.iter().any(Filter::selects_filename)
+            }
+            _ => false,
+        }
+    }
 }
 
 pub trait ProfileParameters {
-    /// Get the game versions present, if self has `GameVersionStrict` or `GameVersionMinor`
+    /// Get the game versions present, if self has `GameVersionStrict`, `GameVersionMinor`, or `GameVersionDevelopment`
     fn game_versions(&self) -> Option<&Vec<String>>;
     /// Get the first mod loader present, if self has `ModLoaderPrefer` or `ModLoaderAny`
     fn mod_loader(&self) -> Option<&ModLoader>;
-    /// Get the game versions present, if self has `GameVersionStrict` or `GameVersionMinor`
+    /// Get the game versions present, if self has `GameVersionStrict`, `GameVersionMinor`, or `GameVersionDevelopment`
     fn game_versions_mut(&mut self) -> Option<&mut Vec<String>>;
     /// Get the mod loaders present, if self has `ModLoaderPrefer` or `ModLoaderAny`
     fn mod_loaders_mut(&mut self) -> Option<&mut Vec<ModLoader>>;
@@ -57,6 +190,7 @@ impl ProfileParameters for Vec<Filter> {
         self.iter().find_map(|filter| match filter {
             Filter::GameVersionStrict(v) => Some(v),
             Filter::GameVersionMinor(v) => Some(v),
+            Filter::GameVersionDevelopment(v) => Some(v),
             _ => None,
         })
     }
@@ -73,6 +207,7 @@ impl ProfileParameters for Vec<Filter> {
         self.iter_mut().find_map(|filter| match filter {
             Filter::GameVersionStrict(v) => Some(v),
             Filter::GameVersionMinor(v) => Some(v),
+            Filter::GameVersionDevelopment(v) => Some(v),
             _ => None,
         })
     }
@@ -98,3 +233,29 @@ pub enum ReleaseChannel {
     Beta,
     Alpha,
 }
+
+/// The order that files are sorted in before the filters are applied
+///
+/// The first file that passes the filters is selected, unless weighted filters are used.
+#[derive(
+    Deserialize, Serialize, Debug, Display, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum,
+)]
+pub enum FileOrder {
+    /// The order returned by the platform, which is usually newest first
+    #[default]
+    Platform,
+    /// Newest files first, by their publish date
+    #[clap(name = "date")]
+    PublishDate,
+    /// Highest mod version first, parsed leniently from the version number
+    #[clap(name = "version")]
+    VersionNumber,
+    /// Featured Modrinth versions first, then in the platform's order
+    Featured,
+}
+
+impl FileOrder {
+    pub(crate) const fn is_platform(&self) -> bool {
+        matches!(self, Self::Platform)
+    }
+}
@@ -0,0 +1,167 @@
+use crate::GITHUB_API;
+use octocrab::{Octocrab, OctocrabBuilder};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::read_to_string,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+};
+use url::Url;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("There is no GitHub host named {0:?} in the config")]
+    UnknownHost(String),
+    #[error("Could not read the token file of GitHub host {0:?}: {1}")]
+    TokenFile(String, std::io::Error),
+    #[error("Could not build the client for GitHub host {0:?}: {1}")]
+    Client(String, octocrab::Error),
+}
+type Result<T> = std::result::Result<T, Error>;
+
+/// A GitHub server, such as a GitHub Enterprise Server, or github.com with a different token
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct GitHubHost {
+    /// The name that identifiers use to refer to this host
+    pub name: String,
+
+    /// The base URL of the REST API (e.g. `https://github.example.com/api/v3`), github.com's API by default
+    ///
+    /// The GraphQL API is expected at `/api/graphql` for `/api/v3` URLs, otherwise at `/graphql`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub api_url: Option<Url>,
+
+    /// The personal access token to authenticate with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub token: Option<String>,
+
+    /// A file to read the personal access token from, used if `token` isn't set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub token_file: Option<PathBuf>,
+
+    /// The repositories (`owner/repo`) or owners that use this host without referring to it by name
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub repositories: Vec<String>,
+}
+
+impl GitHubHost {
+    /// Whether this host is used for the repository `owner/repo` by default
+    fn applies_to(&self, owner: &str, repo: &str) -> bool {
+        self.repositories
+            .iter()
+            .any(|pattern| match pattern.split_once('/') {
+                Some((o, r)) => o.eq_ignore_ascii_case(owner) && r.eq_ignore_ascii_case(repo),
+                None => pattern.eq_ignore_ascii_case(owner),
+            })
+    }
+
+    fn build_client(&self) -> Result<GitHubClient> {
+        let token = match (&self.token, &self.token_file) {
+            (Some(token), _) => Some(token.clone()),
+            (None, Some(path)) => Some(
+                read_to_string(path)
+                    .map_err(|err| Error::TokenFile(self.name.clone(), err))?
+                    .trim()
+                    .to_owned(),
+            ),
+            (None, None) => None,
+        };
+        let build = |base_url: Option<&str>| {
+            let mut builder = OctocrabBuilder::new();
+            if let Some(base_url) = base_url {
+                builder = builder.base_uri(base_url)?;
+            }
+            if let Some(token) = &token {
+                builder = builder.personal_token(token.clone());
+            }
+            builder.build()
+        };
+        let client =
+            |base_url| build(base_url).map_err(|err| Error::Client(self.name.clone(), err));
+
+        Ok(match &self.api_url {
+            Some(api_url) => {
+                let rest = api_url.as_str().trim_end_matches('/');
+                GitHubClient {
+                    rest: client(Some(rest))?,
+                    // Octocrab sends GraphQL queries to `/graphql` relative to the base URL
+                    graphql: client(Some(rest.strip_suffix("/v3").unwrap_or(rest)))?,
+                }
+            }
+            None => {
+                let github = client(None)?;
+                GitHubClient {
+                    rest: github.clone(),
+                    graphql: github,
+                }
+            }
+        })
+    }
+}
+
+/// The clients to use for a GitHub host's REST and GraphQL APIs
+#[derive(Debug, Clone)]
+pub struct GitHubClient {
+    pub rest: Octocrab,
+    pub graphql: Octocrab,
+}
+
+/// The configured GitHub hosts, and the clients that have been built for them
+#[derive(Debug, Default)]
+pub struct GitHubHosts {
+    hosts: Vec<GitHubHost>,
+    /// The clients that have been built, by host name
+    clients: Mutex<HashMap<String, GitHubClient>>,
+}
+
+impl GitHubHosts {
+    pub fn new(hosts: Vec<GitHubHost>) -> Self {
+        Self {
+            hosts,
+            clients: Mutex::default(),
+        }
+    }
+
+    /// Get the name of the host to use for the repository `owner/repo`,
+    /// which is `host` if provided, otherwise the first host that lists the repository or its owner
+    ///
+    /// Returns `None` if github.com should be used with the `GITHUB_TOKEN` environment variable.
+    pub fn host_for(&self, host: Option<&str>, owner: &str, repo: &str) -> Option<String> {
+        host.map(ToOwned::to_owned).or_else(|| {
+            self.hosts
+                .iter()
+                .find(|h| h.applies_to(owner, repo))
+                .map(|h| h.name.clone())
+        })
+    }
+
+    /// Get the client for the repository `owner/repo`, using the host named `host` if provided
+    ///
+    /// See [`GitHubHosts::host_for`] for how the host is chosen.
+    pub fn client(&self, host: Option<&str>, owner: &str, repo: &str) -> Result<GitHubClient> {
+        let Some(name) = self.host_for(host, owner, repo) else {
+            return Ok(GitHubClient {
+                rest: GITHUB_API.clone(),
+                graphql: GITHUB_API.clone(),
+            });
+        };
+
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(client) = clients.get(&name) {
+            return Ok(client.clone());
+        }
+        let client = self
+            .hosts
+            .iter()
+            .find(|h| h.name == name)
+            .ok_or_else(|| Error::UnknownHost(name.clone()))?
+            .build_client()?;
+        clients.insert(name, client.clone());
+        Ok(client)
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{gitlab::GitLab, GITLAB_API};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::read_to_string,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+};
+use url::Url;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("There is no GitLab host named {0:?} in the config")]
+    UnknownHost(String),
+    #[error("Could not read the token file of GitLab host {0:?}: {1}")]
+    TokenFile(String, std::io::Error),
+}
+type Result<T> = std::result::Result<T, Error>;
+
+/// The host name that refers to gitlab.com in identifiers, which configured hosts can't use
+pub const GITLAB_PREFIX: &str = "gitlab";
+
+/// A self-hosted GitLab instance, or gitlab.com with a different token
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct GitLabHost {
+    /// The name that identifiers use to refer to this host
+    pub name: String,
+
+    /// The base URL of the instance (e.g. `https://gitlab.example.com`)
+    pub url: Url,
+
+    /// The personal access token to authenticate with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub token: Option<String>,
+
+    /// A file to read the personal access token from, used if `token` isn't set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub token_file: Option<PathBuf>,
+}
+
+impl GitLabHost {
+    fn build_client(&self) -> Result<GitLab> {
+        let token = match (&self.token, &self.token_file) {
+            (Some(token), _) => Some(token.clone()),
+            (None, Some(path)) => Some(
+                read_to_string(path)
+                    .map_err(|err| Error::TokenFile(self.name.clone(), err))?
+                    .trim()
+                    .to_owned(),
+            ),
+            (None, None) => None,
+        };
+        Ok(GitLab::new(&self.url, token))
+    }
+}
+
+/// The configured GitLab hosts, and the clients that have been built for them
+#[derive(Debug, Default)]
+pub struct GitLabHosts {
+    hosts: Vec<GitLabHost>,
+    /// The clients that have been built, by host name
+    clients: Mutex<HashMap<String, GitLab>>,
+}
+
+impl GitLabHosts {
+    pub fn new(hosts: Vec<GitLabHost>) -> Self {
+        Self {
+            hosts,
+            clients: Mutex::default(),
+        }
+    }
+
+    /// Whether there is a configured GitLab host named `name`
+    pub fn is_host(&self, name: &str) -> bool {
+        self.hosts.iter().any(|h| h.name == name)
+    }
+
+    /// Get the client for the host named `host`, or gitlab.com with the `GITLAB_TOKEN` environment variable
+    pub fn client(&self, host: Option<&str>) -> Result<GitLab> {
+        let Some(name) = host else {
+            return Ok(GITLAB_API.clone());
+        };
+
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(client) = clients.get(name) {
+            return Ok(client.clone());
+        }
+        let client = self
+            .hosts
+            .iter()
+            .find(|h| h.name == name)
+            .ok_or_else(|| Error::UnknownHost(name.to_owned()))?
+            .build_client()?;
+        clients.insert(name.to_owned(), client.clone());
+        Ok(client)
+    }
+}
@@ -1,9 +1,17 @@
+pub mod compatibility;
+pub mod context;
 pub mod filters;
+pub mod github_hosts;
+pub mod gitlab_hosts;
 pub mod structs;
+pub mod version_groups;
+
+pub use context::Context;
 
 use std::{
+    collections::HashSet,
     fs::{create_dir_all, File},
-    io::{BufReader, Result},
+    io::{BufReader, Error, ErrorKind, Result},
     path::{Path, PathBuf},
     sync::LazyLock,
 };
@@ -16,6 +24,10 @@ pub static DEFAULT_CONFIG_PATH: LazyLock<PathBuf> = LazyLock::new(|| {
 });
 
 /// Open the config file at `path` and deserialise it into a config struct
+///
+/// The filters in the config are validated, and the names of its GitHub and GitLab hosts are checked.
+/// Use [`Context::new`] to get the loader compatibility rules, version groups,
+/// GitHub asset inspection option, and GitHub and GitLab hosts to add and upgrade mods with.
 pub fn read_config(path: impl AsRef<Path>) -> Result<structs::Config> {
     if !path.as_ref().exists() {
         create_dir_all(path.as_ref().parent().expect("Invalid config directory"))?;
@@ -25,6 +37,39 @@ pub fn read_config(path: impl AsRef<Path>) -> Result<structs::Config> {
     let config_file = BufReader::new(File::open(&path)?);
     let mut config: structs::Config = serde_json::from_reader(config_file)?;
 
+    // Identifiers refer to GitHub and GitLab hosts by name, so the names have to be unambiguous
+    let mut host_names = HashSet::from([gitlab_hosts::GITLAB_PREFIX]);
+    for name in config
+        .github_hosts
+        .iter()
+        .map(|host| &host.name)
+        .chain(config.gitlab_hosts.iter().map(|host| &host.name))
+    {
+        if !host_names.insert(name) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("The host name {name:?} is reserved or used by more than one host"),
+            ));
+        }
+    }
+
+    // Report invalid filters now instead of in the middle of an upgrade
+    for profile in &config.profiles {
+        for filter in profile
+            .filters
+            .iter()
+            .cloned()
+            .chain(profile.mods.iter().flat_map(structs::Mod::all_filters))
+        {
+            filter.validate().map_err(|err| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid filter {filter} in profile {}: {err}", profile.name),
+                )
+            })?;
+        }
+    }
+
     config
         .profiles
         .iter_mut()
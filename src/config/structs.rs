@@ -1,5 +1,12 @@
-use super::filters::Filter;
+use super::{
+    compatibility::{self, LoaderCompatibility},
+    filters::{FileOrder, Filter},
+    github_hosts::GitHubHost,
+    gitlab_hosts::GitLabHost,
+    version_groups::VersionGroups,
+};
 use derive_more::derive::Display;
+use ferinth::structures::project::ProjectSupportRange;
 use serde::{Deserialize, Serialize};
 use std::{path::PathBuf, str::FromStr};
 
@@ -20,6 +27,52 @@ pub struct Config {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
     pub modpacks: Vec<Modpack>,
+
+    /// Additional rules for which loaders can run files made for other loaders
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub loader_compatibility: Vec<LoaderCompatibility>,
+
+    /// Whether the rules above replace the default rules instead of extending them
+    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default)]
+    pub override_loader_compatibility: bool,
+
+    /// Custom game version groups used by the `GameVersionMinor` filter
+    #[serde(skip_serializing_if = "VersionGroups::is_empty")]
+    #[serde(default)]
+    pub version_groups: VersionGroups,
+
+    /// Whether GitHub release assets are downloaded and inspected to determine their compatibility,
+    /// instead of guessing it from their filenames
+    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default)]
+    pub inspect_github_assets: bool,
+
+    /// GitHub servers (e.g. GitHub Enterprise Servers) and the tokens to use for them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub github_hosts: Vec<GitHubHost>,
+
+    /// Self-hosted GitLab instances and the tokens to use for them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub gitlab_hosts: Vec<GitLabHost>,
+}
+
+impl Config {
+    /// Get the loader compatibility rules to use, combining the config's rules with the defaults if necessary
+    pub fn loader_compatibility_rules(&self) -> Vec<LoaderCompatibility> {
+        if self.override_loader_compatibility {
+            self.loader_compatibility.clone()
+        } else {
+            [
+                compatibility::default_rules(),
+                self.loader_compatibility.clone(),
+            ]
+            .concat()
+        }
+    }
 }
 
 const fn is_zero(n: &usize) -> bool {
@@ -53,6 +106,23 @@ pub struct Profile {
 
     pub mods: Vec<Mod>,
 
+    /// The name of the world to install data packs to, which has to be set to add data packs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub world: Option<String>,
+
+    /// The order that files are sorted in before filtering
+    #[serde(skip_serializing_if = "FileOrder::is_platform")]
+    #[serde(default)]
+    pub file_order: FileOrder,
+
+    /// The side this profile is installed on
+    ///
+    /// If this is set, mods that don't run on this side (e.g. client-only mods on a server) are skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub environment: Option<Environment>,
+
     // Kept for backwards compatibility reasons (i.e. migrating from a v4 config)
     #[serde(skip_serializing)]
     game_version: Option<String>,
@@ -72,26 +142,41 @@ impl Profile {
             name,
             output_dir,
             filters: vec![
-                Filter::ModLoaderPrefer(match mod_loader {
-                    ModLoader::Quilt => vec![ModLoader::Quilt, ModLoader::Fabric],
-                    _ => vec![mod_loader],
-                }),
+                Filter::ModLoaderPrefer(vec![mod_loader]),
                 Filter::GameVersionStrict(game_versions),
             ],
             mods: vec![],
+            world: None,
+            file_order: FileOrder::default(),
+            environment: None,
             game_version: None,
             mod_loader: None,
         }
     }
 
+    /// A constructor for server plugin profiles, which download to the `plugins` directory of `server_dir`
+    pub fn new_plugin(
+        name: String,
+        server_dir: PathBuf,
+        game_versions: Vec<String>,
+        platform: ModLoader,
+    ) -> Self {
+        Self {
+            environment: Some(Environment::Server),
+            ..Self::new(
+                name,
+                server_dir.join(ContentType::Plugin.directory()),
+                game_versions,
+                platform,
+            )
+        }
+    }
+
     /// Convert the v4 profile's `game_version` and `mod_loader` fields into filters
     pub(crate) fn backwards_compat(&mut self) {
         if let (Some(version), Some(loader)) = (self.game_version.take(), self.mod_loader.take()) {
             self.filters = vec![
-                Filter::ModLoaderPrefer(match loader {
-                    ModLoader::Quilt => vec![ModLoader::Quilt, ModLoader::Fabric],
-                    _ => vec![loader],
-                }),
+                Filter::ModLoaderPrefer(vec![loader]),
                 Filter::GameVersionStrict(vec![version]),
             ];
         }
@@ -104,11 +189,42 @@ impl Profile {
         }
     }
 
+    /// Get the directory that files of `content_type` should be downloaded to
+    ///
+    /// Mods are downloaded to `output_dir`, and other content is downloaded to the appropriate
+    /// directory in the instance directory (i.e. the parent of `output_dir`).
+    pub fn output_dir_for(&self, content_type: ContentType) -> PathBuf {
+        let instance_dir = self.output_dir.parent().unwrap_or(&self.output_dir);
+        match (content_type, &self.world) {
+            (ContentType::Mod, _) => self.output_dir.clone(),
+            (ContentType::DataPack, Some(world)) => instance_dir
+                .join("saves")
+                .join(world)
+                .join(content_type.directory()),
+            _ => instance_dir.join(content_type.directory()),
+        }
+    }
+
+    /// Get the mods that should be installed on this profile's environment
+    ///
+    /// All mods are returned if the profile's environment is not set.
+    pub fn mods_to_install(&self) -> impl Iterator<Item = &Mod> {
+        self.mods
+            .iter()
+            .filter(|mod_| match (mod_.environment, self.environment) {
+                (Some(environment), Some(side)) => environment.runs_on(side),
+                _ => true,
+            })
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn push_mod(
         &mut self,
         name: String,
         identifier: ModIdentifier,
         slug: String,
+        content_type: ContentType,
+        environment: Option<Environment>,
         override_filters: bool,
         filters: Vec<Filter>,
     ) {
@@ -116,8 +232,12 @@ impl Profile {
             name,
             slug: Some(slug),
             identifier,
+            content_type,
+            environment,
             filters,
             override_filters,
+            version_requirement: None,
+            file_order: None,
             check_game_version: None,
             check_mod_loader: None,
         })
@@ -134,6 +254,16 @@ pub struct Mod {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub slug: Option<String>,
 
+    /// The type of content this project provides
+    #[serde(skip_serializing_if = "ContentType::is_mod")]
+    #[serde(default)]
+    pub content_type: ContentType,
+
+    /// The side(s) of the game this mod runs on, if it is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub environment: Option<Environment>,
+
     /// Custom filters that apply only for this mod
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
@@ -144,6 +274,16 @@ pub struct Mod {
     #[serde(default)]
     pub override_filters: bool,
 
+    /// A requirement on the mod's version number (e.g. `^0.5`), which applies even if the filters are overridden
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub version_requirement: Option<String>,
+
+    /// Overrides the profile's file order for this mod
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub file_order: Option<FileOrder>,
+
     // Kept for backwards compatibility reasons
     #[serde(skip_serializing)]
     check_game_version: Option<bool>,
@@ -152,6 +292,13 @@ pub struct Mod {
 }
 
 impl Mod {
+    /// Get this mod's filters, including the filter for its version requirement
+    pub fn all_filters(&self) -> Vec<Filter> {
+        let mut filters = self.filters.clone();
+        filters.extend(self.version_requirement.clone().map(Filter::ModVersion));
+        filters
+    }
+
     pub fn new(
         name: String,
         identifier: ModIdentifier,
@@ -162,8 +309,12 @@ impl Mod {
             name,
             slug: None,
             identifier,
+            content_type: ContentType::Mod,
+            environment: None,
             filters,
             override_filters,
+            version_requirement: None,
+            file_order: None,
             check_game_version: None,
             check_mod_loader: None,
         }
@@ -179,25 +330,261 @@ pub enum ModIdentifier {
     CurseForgeProject(i32),
     ModrinthProject(String),
     GitHubRepository(String, String),
+    /// A repository on the configured GitHub host named `host`
+    GitHubHostRepository {
+        host: String,
+        repo: (String, String),
+    },
+
+    /// A GitLab project by its ID or path (e.g. `group/project`),
+    /// on the configured GitLab host named `host` or gitlab.com
+    GitLabProject {
+        project: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        host: Option<String>,
+    },
 
     PinnedCurseForgeProject(i32, i32),
     PinnedModrinthProject(String, String),
+    /// Pinned to a release asset by its ID, prefer `PinnedGitHubRelease` instead
     PinnedGitHubRepository((String, String), i32),
+    /// Pinned to the release with `tag`
+    ///
+    /// The asset with a filename matching the `asset` regex is used if provided,
+    /// otherwise the asset is selected using the filters.
+    PinnedGitHubRelease {
+        repo: (String, String),
+        tag: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        asset: Option<String>,
+        /// The name of the configured GitHub host the repository is on
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        host: Option<String>,
+    },
+    /// Pinned to the GitLab release with `tag`, the asset is selected like in `PinnedGitHubRelease`
+    PinnedGitLabRelease {
+        project: String,
+        tag: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        asset: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        host: Option<String>,
+    },
+}
+
+#[derive(
+    Deserialize, Serialize, Debug, Display, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum,
+)]
+pub enum ContentType {
+    #[default]
+    Mod,
+    #[clap(name = "resourcepack")]
+    ResourcePack,
+    Shader,
+    #[clap(name = "datapack")]
+    DataPack,
+    Plugin,
 }
 
+impl ContentType {
+    const fn is_mod(&self) -> bool {
+        matches!(self, Self::Mod)
+    }
+
+    /// The name of the directory that files of this type are installed to
+    pub const fn directory(&self) -> &'static str {
+        match self {
+            Self::Mod => "mods",
+            Self::ResourcePack => "resourcepacks",
+            Self::Shader => "shaderpacks",
+            Self::DataPack => "datapacks",
+            Self::Plugin => "plugins",
+        }
+    }
+
+    /// Whether the profile's mod loader filters apply to this type of content
+    ///
+    /// Resource packs and data packs don't have loaders, and shaders are loaded by shader loaders
+    /// (e.g. Iris or OptiFine), so these can only be filtered by loader using the project's own filters.
+    pub const fn uses_profile_loaders(&self) -> bool {
+        matches!(self, Self::Mod | Self::Plugin)
+    }
+
+    /// Whether the profile's `filter` applies to this type of content
+    ///
+    /// Filters that [use loaders](Filter::uses_loaders), including combinations containing them,
+    /// only apply to content that uses the profile's loaders.
+    pub fn uses_profile_filter(&self, filter: &Filter) -> bool {
+        self.uses_profile_loaders() || !filter.uses_loaders()
+    }
+
+    /// Remove the filters in `filters` that don't apply to this type of content
+    pub fn applicable_filters(&self, mut filters: Vec<Filter>) -> Vec<Filter> {
+        filters.retain(|filter| self.uses_profile_filter(filter));
+        filters
+    }
+}
+
+/// The side(s) of the game that a mod runs on
 #[derive(Deserialize, Serialize, Debug, Display, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Environment {
+    Client,
+    Server,
+    Both,
+}
+
+impl Environment {
+    /// Determine the environment from a Modrinth project's client and server support
+    ///
+    /// Returns `None` if the environment is unknown.
+    pub fn from_support(
+        client: &ProjectSupportRange,
+        server: &ProjectSupportRange,
+    ) -> Option<Self> {
+        use ProjectSupportRange::*;
+        match (client, server) {
+            (Unsupported, Unsupported) | (Unknown, Unknown) => None,
+            (Unsupported, _) => Some(Self::Server),
+            (_, Unsupported) => Some(Self::Client),
+            _ => Some(Self::Both),
+        }
+    }
+
+    /// Whether files for this environment run on `side`
+    pub fn runs_on(&self, side: Self) -> bool {
+        *self == Self::Both || *self == side
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Display, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
 pub enum ModLoader {
     Quilt,
     Fabric,
     Forge,
-    #[clap(name = "neoforge")]
     NeoForge,
+
+    // Shader loaders
+    Iris,
+    OptiFine,
+    Canvas,
+
+    // Server plugin platforms
+    Bukkit,
+    Spigot,
+    Paper,
+    Purpur,
+    Folia,
+    Velocity,
+    BungeeCord,
+    Waterfall,
+
+    /// A loader that libium doesn't recognise, stored in lowercase
+    ///
+    /// This allows files for loaders such as LiteLoader, Rift, or Babric to still be filtered.
+    Other(String),
+}
+
+/// The loaders recognised by libium, i.e. every variant except `Other`
+static KNOWN_LOADERS: [ModLoader; 15] = [
+    ModLoader::Quilt,
+    ModLoader::Fabric,
+    ModLoader::Forge,
+    ModLoader::NeoForge,
+    ModLoader::Iris,
+    ModLoader::OptiFine,
+    ModLoader::Canvas,
+    ModLoader::Bukkit,
+    ModLoader::Spigot,
+    ModLoader::Paper,
+    ModLoader::Purpur,
+    ModLoader::Folia,
+    ModLoader::Velocity,
+    ModLoader::BungeeCord,
+    ModLoader::Waterfall,
+];
+
+impl ModLoader {
+    /// Whether this is a server plugin platform rather than a mod loader
+    pub const fn is_plugin_loader(&self) -> bool {
+        matches!(
+            self,
+            Self::Bukkit
+                | Self::Spigot
+                | Self::Paper
+                | Self::Purpur
+                | Self::Folia
+                | Self::Velocity
+                | Self::BungeeCord
+                | Self::Waterfall
+        )
+    }
+
+    /// Get the loaders whose files can run on this loader for `game_versions` using the `rules`, in order of preference
+    ///
+    /// The first element is always `self`.
+    pub fn compatible_loaders(
+        &self,
+        rules: &[LoaderCompatibility],
+        game_versions: &[String],
+    ) -> Vec<Self> {
+        compatibility::compatible_loaders(rules, self, game_versions)
+    }
+}
+
+impl clap::ValueEnum for ModLoader {
+    fn value_variants<'a>() -> &'a [Self] {
+        &KNOWN_LOADERS
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        Some(clap::builder::PossibleValue::new(match self {
+            Self::Quilt => "quilt",
+            Self::Fabric => "fabric",
+            Self::Forge => "forge",
+            Self::NeoForge => "neoforge",
+            Self::Iris => "iris",
+            Self::OptiFine => "optifine",
+            Self::Canvas => "canvas",
+            Self::Bukkit => "bukkit",
+            Self::Spigot => "spigot",
+            Self::Paper => "paper",
+            Self::Purpur => "purpur",
+            Self::Folia => "folia",
+            Self::Velocity => "velocity",
+            Self::BungeeCord => "bungeecord",
+            Self::Waterfall => "waterfall",
+            Self::Other(_) => return None,
+        }))
+    }
 }
 
 #[derive(thiserror::Error, Debug, PartialEq, Eq)]
 #[error("The given string is not a recognised mod loader")]
 pub struct ModLoaderParseError;
 
+/// Parses a recognised loader, or stores the loader as `Other` if it isn't recognised
+///
+/// Use the `FromStr` implementation instead when parsing strings that may not be loaders (e.g. filenames).
+impl From<&str> for ModLoader {
+    fn from(from: &str) -> Self {
+        from.parse()
+            .unwrap_or_else(|_| Self::Other(from.trim().to_lowercase()))
+    }
+}
+
+impl From<String> for ModLoader {
+    fn from(from: String) -> Self {
+        from.as_str().into()
+    }
+}
+
+impl From<ModLoader> for String {
+    fn from(from: ModLoader) -> Self {
+        from.to_string()
+    }
+}
+
 impl FromStr for ModLoader {
     type Err = ModLoaderParseError;
 
@@ -208,6 +595,17 @@ impl FromStr for ModLoader {
             "fabric" => Ok(Self::Fabric),
             "forge" => Ok(Self::Forge),
             "neoforge" => Ok(Self::NeoForge),
+            "iris" => Ok(Self::Iris),
+            "optifine" => Ok(Self::OptiFine),
+            "canvas" => Ok(Self::Canvas),
+            "bukkit" => Ok(Self::Bukkit),
+            "spigot" => Ok(Self::Spigot),
+            "paper" => Ok(Self::Paper),
+            "purpur" => Ok(Self::Purpur),
+            "folia" => Ok(Self::Folia),
+            "velocity" => Ok(Self::Velocity),
+            "bungeecord" => Ok(Self::BungeeCord),
+            "waterfall" => Ok(Self::Waterfall),
             _ => Err(Self::Err {}),
         }
     }
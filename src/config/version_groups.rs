@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+/// Custom groups of game versions that are considered minor updates in terms of mod compatibility
+///
+/// These take precedence over the groups determined from the game version list.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct VersionGroups {
+    /// Groups of game versions, versions in these groups are removed from the computed groups
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub groups: Vec<Vec<String>>,
+
+    /// Additional game versions that start a new group (e.g. `1.20.5`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub boundaries: Vec<String>,
+}
+
+impl VersionGroups {
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty() && self.boundaries.is_empty()
+    }
+
+    /// Returns whether `version` is configured to start a new group
+    pub fn is_boundary(&self, version: &str) -> bool {
+        self.boundaries.iter().any(|v| v == version)
+    }
+
+    /// Apply the custom groups to the `computed` groups
+    ///
+    /// The custom groups come first, and their versions are removed from the computed groups.
+    pub fn apply(&self, computed: Vec<Vec<String>>) -> Vec<Vec<String>> {
+        self.groups
+            .iter()
+            .cloned()
+            .chain(computed.into_iter().map(|mut group| {
+                group.retain(|v| self.groups.iter().all(|g| !g.contains(v)));
+                group
+            }))
+            .filter(|group| !group.is_empty())
+            .collect()
+    }
+}
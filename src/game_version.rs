@@ -0,0 +1,630 @@
+use crate::MODRINTH_API;
+use chrono::{DateTime, NaiveDate, Utc, Weekday};
+use ferinth::structures::tag::{GameVersion as GameVersionTag, GameVersionType};
+use regex::Regex;
+use serde::Deserialize;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{self, Display},
+    fs::{create_dir_all, read_to_string, write},
+    path::Path,
+    str::FromStr,
+    sync::LazyLock,
+    time::Duration,
+};
+
+/// The list of game versions, in chronological order
+#[derive(Debug)]
+pub struct Manifest {
+    pub versions: Vec<GameVersionTag>,
+    positions: HashMap<String, usize>,
+    /// The newest release in the manifest
+    newest_release: Option<GameVersion>,
+    /// The newest weekly snapshot in the manifest
+    newest_snapshot: Option<GameVersion>,
+}
+
+impl Manifest {
+    /// Create a manifest from a list of game versions in reverse chronological order (as Modrinth provides them)
+    pub fn new(mut versions: Vec<GameVersionTag>) -> Self {
+        // Reverse before sorting to keep the order of versions released at the same time
+        versions.reverse();
+        versions.sort_by_key(|v| v.date);
+        let positions = versions
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.version.clone(), i))
+            .collect();
+        let mut newest = versions
+            .iter()
+            .rev()
+            .map(|v| (v, v.version.parse::<GameVersion>()));
+        let newest_release = newest
+            .clone()
+            .find(|(v, _)| v.version_type == GameVersionType::Release)
+            .and_then(|(_, parsed)| parsed.ok());
+        let newest_snapshot = newest.find_map(|(_, parsed)| {
+            parsed
+                .ok()
+                .filter(|parsed| matches!(parsed, GameVersion::Snapshot { .. }))
+        });
+        Self {
+            versions,
+            positions,
+            newest_release,
+            newest_snapshot,
+        }
+    }
+
+    /// Get the chronological position of `version`, if it is present in the manifest
+    pub fn position(&self, version: &str) -> Option<usize> {
+        self.positions.get(version).copied()
+    }
+
+    /// Whether `version` is in the manifest, or is newer than the versions in it
+    ///
+    /// Versions that aren't in the manifest are only accepted if they're releases newer than (and with the same
+    /// first number as) the newest release, or weekly snapshots newer than the newest snapshot,
+    /// so that game versions released after the manifest was fetched are still recognised.
+    pub fn is_known(&self, version: &str) -> bool {
+        if self.positions.contains_key(version) {
+            return true;
+        }
+        let Ok(parsed) = version.parse::<GameVersion>();
+        let newest = match parsed {
+            GameVersion::Release { ref numbers, .. } => self.newest_release.as_ref().filter(|newest| {
+                matches!(newest, GameVersion::Release { numbers: newest, .. } if newest.first() == numbers.first())
+            }),
+            GameVersion::Snapshot { .. } => self.newest_snapshot.as_ref(),
+            GameVersion::Other(_) => None,
+        };
+        newest.is_some_and(|newest| parsed.partial_cmp_format(newest) == Some(Ordering::Greater))
+    }
+}
+
+/// The game versions bundled with libium, used if the game versions can't be fetched or read from the cache
+///
+/// These are all the releases, and the snapshots, pre-releases, and release candidates since 1.20.
+const BUNDLED_VERSIONS: &str = include_str!("game_versions.json");
+/// How long the cached game versions are used before they are fetched again
+const CACHE_LIFETIME: Duration = Duration::from_secs(24 * 60 * 60);
+const MOJANG_MANIFEST_URL: &str = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
+/// The name of the file that the game versions are cached in
+pub const CACHE_FILE: &str = "game_versions.json";
+
+/// Loads the list of game versions, caching them in [`CACHE_FILE`] in `cache_dir` if provided
+///
+/// The versions are read from the cache if it was recently updated,
+/// otherwise they are fetched from Modrinth (or Mojang's version manifest if Modrinth is unreachable) and cached.
+/// If the versions can't be fetched, the outdated cache or the versions bundled with libium are used instead,
+/// so this works offline.
+///
+/// Use [`Context::manifest`](crate::config::Context::manifest) to only load the versions once.
+pub async fn load_manifest(cache_dir: Option<&Path>) -> Manifest {
+    let cache_path = cache_dir.map(|dir| dir.join(CACHE_FILE));
+    let cache_path = cache_path.as_deref();
+    let versions = if let Some(versions) = cache_path.and_then(|path| read_cache(path, true)) {
+        versions
+    } else if let Some(versions) = fetch_versions().await {
+        if let Some(path) = cache_path {
+            if let Some(dir) = path.parent() {
+                let _ = create_dir_all(dir);
+            }
+            if let Ok(json) = serde_json::to_string(&versions) {
+                let _ = write(path, json);
+            }
+        }
+        versions
+    } else {
+        cache_path
+            .and_then(|path| read_cache(path, false))
+            .unwrap_or_else(bundled_versions)
+    };
+
+    Manifest::new(versions)
+}
+
+/// Read the game versions cached at `path`, if they exist and (if `fresh`) were updated recently
+fn read_cache(path: &Path, fresh: bool) -> Option<Vec<GameVersionTag>> {
+    if fresh {
+        let age = path.metadata().ok()?.modified().ok()?.elapsed().ok()?;
+        if age > CACHE_LIFETIME {
+            return None;
+        }
+    }
+    serde_json::from_str(&read_to_string(path).ok()?).ok()
+}
+
+pub fn bundled_versions() -> Vec<GameVersionTag> {
+    serde_json::from_str(BUNDLED_VERSIONS).expect("Bundled game versions are invalid")
+}
+
+async fn fetch_versions() -> Option<Vec<GameVersionTag>> {
+    if let Ok(versions) = MODRINTH_API.list_game_versions().await {
+        Some(versions)
+    } else {
+        fetch_mojang_versions().await
+    }
+}
+
+#[derive(Deserialize)]
+struct MojangManifest {
+    versions: Vec<MojangVersion>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MojangVersion {
+    id: String,
+    #[serde(rename = "type")]
+    type_: String,
+    release_time: DateTime<Utc>,
+}
+
+/// Fetch the game versions from Mojang's version manifest
+///
+/// Mojang doesn't mark major versions, so releases that change the second number (e.g. `1.21`) are considered major.
+async fn fetch_mojang_versions() -> Option<Vec<GameVersionTag>> {
+    let response = reqwest::get(MOJANG_MANIFEST_URL).await.ok()?;
+    let mut manifest: MojangManifest = serde_json::from_str(&response.text().await.ok()?).ok()?;
+    manifest.versions.sort_by_key(|v| v.release_time);
+
+    let mut previous_release = None;
+    let mut versions = manifest
+        .versions
+        .into_iter()
+        .map(|v| {
+            let version_type = match v.type_.as_str() {
+                "release" => GameVersionType::Release,
+                "old_beta" => GameVersionType::Beta,
+                "old_alpha" => GameVersionType::Alpha,
+                _ => GameVersionType::Snapshot,
+            };
+            let major = version_type == GameVersionType::Release && {
+                let minor = v.id.split('.').take(2).collect::<Vec<_>>().join(".");
+                previous_release.replace(minor.clone()) != Some(minor)
+            };
+            GameVersionTag {
+                version: v.id,
+                version_type,
+                date: v.release_time,
+                major,
+            }
+        })
+        .collect::<Vec<_>>();
+    // Use the same order as Modrinth
+    versions.reverse();
+    Some(versions)
+}
+
+/// The development stage of a release version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    PreRelease(u32),
+    ReleaseCandidate(u32),
+    Release,
+}
+
+/// A parsed Minecraft version
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameVersion {
+    /// A release (e.g. `1.20.1`), or a pre-release or release candidate of one (e.g. `1.20.5-pre1` or `1.20.5-rc1`)
+    Release { numbers: Vec<u32>, stage: Stage },
+    /// A weekly snapshot (e.g. `24w14a`)
+    Snapshot { year: u32, week: u32, build: char },
+    /// A version that doesn't follow a known format (e.g. April Fools versions)
+    ///
+    /// These can only be compared using the manifest.
+    Other(String),
+}
+
+static RELEASE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(\d+(?:\.\d+)+)(?:(?:-pre| Pre-Release )(\d+)|-rc(\d+))?$").unwrap()
+});
+static SNAPSHOT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d{2})w(\d{2})([a-z])$").unwrap());
+
+impl FromStr for GameVersion {
+    type Err = std::convert::Infallible;
+
+    fn from_str(from: &str) -> Result<Self, Self::Err> {
+        let from = from.trim();
+        Ok(if let Some(captures) = RELEASE_REGEX.captures(from) {
+            GameVersion::Release {
+                numbers: captures[1]
+                    .split('.')
+                    .map(|n| n.parse().unwrap_or_default())
+                    .collect(),
+                stage: match (captures.get(2), captures.get(3)) {
+                    (Some(n), _) => Stage::PreRelease(n.as_str().parse().unwrap_or_default()),
+                    (_, Some(n)) => Stage::ReleaseCandidate(n.as_str().parse().unwrap_or_default()),
+                    _ => Stage::Release,
+                },
+            }
+        } else if let Some(captures) = SNAPSHOT_REGEX.captures(from) {
+            GameVersion::Snapshot {
+                year: captures[1].parse().unwrap_or_default(),
+                week: captures[2].parse().unwrap_or_default(),
+                build: captures[3].chars().next().unwrap_or_default(),
+            }
+        } else {
+            GameVersion::Other(from.to_owned())
+        })
+    }
+}
+
+impl GameVersion {
+    /// Compare `self` and `other` using their formats, without the manifest
+    ///
+    /// Returns `None` if they have different formats, since (for example) the release a snapshot leads up to
+    /// can't be determined from the snapshot alone.
+    pub fn partial_cmp_format(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (
+                Self::Release { numbers, stage },
+                Self::Release {
+                    numbers: other_numbers,
+                    stage: other_stage,
+                },
+            ) => {
+                // Pad the shorter version with zeroes, since `1.20` is the same as `1.20.0`
+                let len = numbers.len().max(other_numbers.len());
+                let pad = |n: &Vec<u32>| {
+                    n.iter()
+                        .copied()
+                        .chain(std::iter::repeat(0))
+                        .take(len)
+                        .collect::<Vec<_>>()
+                };
+                Some(
+                    pad(numbers)
+                        .cmp(&pad(other_numbers))
+                        .then(stage.cmp(other_stage)),
+                )
+            }
+            (
+                Self::Snapshot { year, week, build },
+                Self::Snapshot {
+                    year: other_year,
+                    week: other_week,
+                    build: other_build,
+                },
+            ) => Some((year, week, build).cmp(&(other_year, other_week, other_build))),
+            (Self::Other(a), Self::Other(b)) if a == b => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+/// A game version that is resolved using the game version list when filtering
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolicVersion {
+    /// `latest-release`, the newest release
+    LatestRelease,
+    /// `latest-snapshot`, the newest version of any kind (including releases)
+    LatestSnapshot,
+    /// `latest-in-<version>`, the newest release in a version line (e.g. `latest-in-1.21`)
+    LatestIn(String),
+}
+
+impl SymbolicVersion {
+    /// Parse `version` if it is symbolic
+    pub fn parse(version: &str) -> Option<Self> {
+        match version {
+            "latest-release" => Some(Self::LatestRelease),
+            "latest-snapshot" => Some(Self::LatestSnapshot),
+            _ => version
+                .strip_prefix("latest-in-")
+                .filter(|line| !line.is_empty())
+                .map(|line| Self::LatestIn(line.to_owned())),
+        }
+    }
+
+    /// Get the concrete version this resolves to in the `manifest`
+    pub fn resolve(&self, manifest: &Manifest) -> Option<String> {
+        let mut versions = manifest.versions.iter().rev();
+        match self {
+            Self::LatestRelease => versions.find(|v| v.version_type == GameVersionType::Release),
+            Self::LatestSnapshot => versions.next(),
+            Self::LatestIn(line) => versions.find(|v| {
+                v.version_type == GameVersionType::Release
+                    && (v.version == *line
+                        || v.version
+                            .strip_prefix(line.as_str())
+                            .is_some_and(|rest| rest.starts_with('.')))
+            }),
+        }
+        .map(|v| v.version.clone())
+    }
+}
+
+/// Get the release that `version` leads up to (e.g. `1.20.5` for `24w14a`, `1.20.5-pre1`, or `1.20.5`)
+///
+/// Versions that aren't releases, pre-releases, or release candidates are mapped to the first release after them
+/// in the `manifest`. Snapshots that aren't in the manifest are mapped using the week they were released in.
+pub fn target_release(version: &str, manifest: Option<&Manifest>) -> Option<String> {
+    let parsed = version.parse::<GameVersion>().ok()?;
+    if let GameVersion::Release { numbers, .. } = parsed {
+        return Some(
+            numbers
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("."),
+        );
+    }
+
+    let manifest = manifest?;
+    let is_release = |v: &&GameVersionTag| v.version_type == GameVersionType::Release;
+    if let Some(position) = manifest.position(version) {
+        manifest.versions[position..]
+            .iter()
+            .find(is_release)
+            .map(|v| v.version.clone())
+    } else if let GameVersion::Snapshot { year, week, .. } = parsed {
+        let date = NaiveDate::from_isoywd_opt(2000 + year as i32, week, Weekday::Mon)?;
+        manifest
+            .versions
+            .iter()
+            .filter(is_release)
+            .find(|v| v.date.date_naive() > date)
+            .map(|v| v.version.clone())
+    } else {
+        None
+    }
+}
+
+/// Get the game versions in a filename (e.g. `1.20.1` in `sodium-fabric-0.5.8+mc1.20.1.jar`)
+///
+/// Tokens are separated by `-`, `_`, or `+`, and may be prefixed with `mc`.
+/// Pre-releases and release candidates split across tokens (e.g. `1.20.5-pre1`) are joined back together.
+/// Only versions [known](Manifest::is_known) to the `manifest` are returned,
+/// so that mod versions (e.g. `0.5.8`) aren't mistaken for game versions.
+pub fn versions_in_filename(filename: &str, manifest: &Manifest) -> Vec<String> {
+    let name = filename
+        .rsplit_once('.')
+        .filter(|(_, extension)| matches!(*extension, "jar" | "zip"))
+        .map_or(filename, |(name, _)| name);
+
+    let mut versions: Vec<String> = vec![];
+    for token in name.split(['-', '_', '+']) {
+        let token = token
+            .strip_prefix("mc")
+            .or_else(|| token.strip_prefix("MC"))
+            .unwrap_or(token);
+        if token.starts_with("pre") || token.starts_with("rc") {
+            if let Some(last) = versions.last_mut() {
+                let joined = format!("{last}-{token}");
+                if matches!(
+                    joined.parse(),
+                    Ok(GameVersion::Release {
+                        stage: Stage::PreRelease(_) | Stage::ReleaseCandidate(_),
+                        ..
+                    })
+                ) {
+                    *last = joined;
+                    continue;
+                }
+            }
+        }
+        if manifest.is_known(token) {
+            versions.push(token.to_owned());
+        }
+    }
+    versions
+}
+
+/// Compare the game versions `a` and `b` chronologically
+///
+/// The `manifest` is used if both versions are present in it, otherwise the versions' formats are compared.
+pub fn compare(a: &str, b: &str, manifest: Option<&Manifest>) -> Option<Ordering> {
+    if let Some(manifest) = manifest {
+        if let (Some(a), Some(b)) = (manifest.position(a), manifest.position(b)) {
+            return Some(a.cmp(&b));
+        }
+    }
+    a.parse::<GameVersion>()
+        .ok()?
+        .partial_cmp_format(&b.parse().ok()?)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Less,
+    LessEqual,
+    Equal,
+    GreaterEqual,
+    Greater,
+}
+
+/// A comparison against a game version, e.g. `>=1.20.2`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparator {
+    pub operator: Operator,
+    pub version: String,
+}
+
+/// A range of game versions, written as comparators separated by commas or spaces (e.g. `>=1.20.2, <=1.20.6`)
+///
+/// A version without an operator has to match exactly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameVersionRange(pub Vec<Comparator>);
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error("{0:?} is not a valid game version range")]
+pub struct RangeParseError(pub String);
+
+impl FromStr for GameVersionRange {
+    type Err = RangeParseError;
+
+    fn from_str(from: &str) -> Result<Self, Self::Err> {
+        let comparators = from
+            .split([',', ' '])
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                let (operator, version) = if let Some(v) = s.strip_prefix(">=") {
+                    (Operator::GreaterEqual, v)
+                } else if let Some(v) = s.strip_prefix("<=") {
+                    (Operator::LessEqual, v)
+                } else if let Some(v) = s.strip_prefix('>') {
+                    (Operator::Greater, v)
+                } else if let Some(v) = s.strip_prefix('<') {
+                    (Operator::Less, v)
+                } else {
+                    (Operator::Equal, s.strip_prefix('=').unwrap_or(s))
+                };
+                if version.is_empty() {
+                    Err(RangeParseError(from.to_owned()))
+                } else {
+                    Ok(Comparator {
+                        operator,
+                        version: version.to_owned(),
+                    })
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if comparators.is_empty() {
+            Err(RangeParseError(from.to_owned()))
+        } else {
+            Ok(Self(comparators))
+        }
+    }
+}
+
+impl Display for GameVersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, comparator) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            let operator = match comparator.operator {
+                Operator::Less => "<",
+                Operator::LessEqual => "<=",
+                Operator::Equal => "=",
+                Operator::GreaterEqual => ">=",
+                Operator::Greater => ">",
+            };
+            write!(f, "{operator}{}", comparator.version)?;
+        }
+        Ok(())
+    }
+}
+
+impl GameVersionRange {
+    /// Returns whether `version` satisfies all the comparators
+    ///
+    /// Versions that can't be compared to a comparator's version don't satisfy it.
+    pub fn contains(&self, version: &str, manifest: Option<&Manifest>) -> bool {
+        self.0.iter().all(|comparator| {
+            compare(version, &comparator.version, manifest).is_some_and(|ordering| match comparator
+                .operator
+            {
+                Operator::Less => ordering.is_lt(),
+                Operator::LessEqual => ordering.is_le(),
+                Operator::Equal => ordering.is_eq(),
+                Operator::GreaterEqual => ordering.is_ge(),
+                Operator::Greater => ordering.is_gt(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> Manifest {
+        Manifest::new(bundled_versions())
+    }
+
+    #[test]
+    fn orders_development_stages() {
+        let cmp = |a, b| compare(a, b, None);
+
+        assert_eq!(cmp("1.20.5-pre1", "1.20.5-pre2"), Some(Ordering::Less));
+        assert_eq!(cmp("1.20.5-pre4", "1.20.5-rc1"), Some(Ordering::Less));
+        assert_eq!(cmp("1.20.5-rc3", "1.20.5"), Some(Ordering::Less));
+        assert_eq!(cmp("1.20.4", "1.20.5-pre1"), Some(Ordering::Less));
+        assert_eq!(cmp("1.20", "1.20.0"), Some(Ordering::Equal));
+        assert_eq!(cmp("1.9", "1.10"), Some(Ordering::Less));
+        assert_eq!(cmp("24w13a", "24w14a"), Some(Ordering::Less));
+    }
+
+    #[test]
+    fn orders_snapshots_and_releases() {
+        let manifest = manifest();
+
+        // Snapshots can't be compared to releases without the manifest
+        assert_eq!(compare("24w14a", "1.20.4", None), None);
+        assert_eq!(
+            compare("24w14a", "1.20.4", Some(&manifest)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            compare("24w14a", "1.20.5-pre1", Some(&manifest)),
+            Some(Ordering::Less)
+        );
+    }
+
+    #[test]
+    fn parses_ranges() {
+        let range = ">=1.20.2, <=1.20.6".parse::<GameVersionRange>().unwrap();
+        assert!(range.contains("1.20.4", None));
+        assert!(!range.contains("1.21", None));
+        assert_eq!(range.to_string(), ">=1.20.2, <=1.20.6");
+
+        let error = |range: &str| Err(RangeParseError(range.to_owned()));
+        assert_eq!("".parse::<GameVersionRange>(), error(""));
+        assert_eq!(" , ".parse::<GameVersionRange>(), error(" , "));
+        assert_eq!(">=1.20 <".parse::<GameVersionRange>(), error(">=1.20 <"));
+    }
+
+    #[test]
+    fn finds_target_releases() {
+        let manifest = manifest();
+        let target = |version| target_release(version, Some(&manifest));
+
+        assert_eq!(target("1.20.5-pre1").as_deref(), Some("1.20.5"));
+        assert_eq!(target("1.20.5").as_deref(), Some("1.20.5"));
+        assert_eq!(target("24w14a").as_deref(), Some("1.20.5"));
+        // Snapshots missing from the manifest are mapped using their week
+        assert_eq!(target("24w14b").as_deref(), Some("1.20.5"));
+        assert_eq!(target_release("24w14a", None), None);
+    }
+
+    #[test]
+    fn finds_versions_in_filenames() {
+        let manifest = manifest();
+        let versions = |filename| versions_in_filename(filename, &manifest);
+
+        assert_eq!(versions("sodium-fabric-0.5.8+mc1.20.1.jar"), ["1.20.1"]);
+        assert_eq!(versions("mod-1.20.5-pre1.jar"), ["1.20.5-pre1"]);
+        assert_eq!(versions("mod_MC1.21.5-rc1_2.0.jar"), ["1.21.5-rc1"]);
+        assert_eq!(versions("mod-25w21a.zip"), ["25w21a"]);
+        assert_eq!(versions("mod-1.20.1-1.20.4.jar"), ["1.20.1", "1.20.4"]);
+    }
+
+    #[test]
+    fn ignores_mod_versions_in_filenames() {
+        let manifest = manifest();
+        let versions = |filename| versions_in_filename(filename, &manifest);
+
+        assert!(versions("mymod-1.4.jar").is_empty());
+        assert!(versions("mod-1.16.22.jar").is_empty());
+        assert!(versions("mod-2.3.jar").is_empty());
+        assert!(versions("sodium-0.5.8.jar").is_empty());
+    }
+
+    #[test]
+    fn accepts_versions_newer_than_manifest() {
+        let manifest = manifest();
+
+        assert!(manifest.is_known("1.21.9"));
+        assert!(manifest.is_known("1.22-pre1"));
+        assert!(manifest.is_known("99w01a"));
+        assert!(!manifest.is_known("1.21.3.1"));
+        assert!(!manifest.is_known("20w01a"));
+    }
+}
@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION},
+    Client, StatusCode, Url,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub enum Error {
+    ReqwestError(#[from] reqwest::Error),
+    JSONError(#[from] serde_json::Error),
+    #[error("The GitLab project or release does not exist")]
+    NotFound,
+}
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Release {
+    pub name: Option<String>,
+    pub tag_name: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+    /// Whether the release is scheduled for the future
+    #[serde(default)]
+    pub upcoming_release: bool,
+    pub assets: ReleaseAssets,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReleaseAssets {
+    #[serde(default)]
+    pub links: Vec<ReleaseLink>,
+}
+
+/// A file attached to a release
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReleaseLink {
+    pub id: u64,
+    pub name: String,
+    pub url: Url,
+    /// The permanent URL that redirects to `url`
+    pub direct_asset_url: Option<Url>,
+}
+
+/// A client for the REST API of a GitLab instance
+#[derive(Debug, Clone)]
+pub struct GitLab {
+    client: Client,
+    api_url: Url,
+    token: Option<String>,
+}
+
+impl GitLab {
+    /// Create a client for the GitLab instance at `url` (e.g. `https://gitlab.com`),
+    /// authenticating with the personal access `token` if provided
+    pub fn new(url: &Url, token: Option<String>) -> Self {
+        let mut api_url = url.clone();
+        if let Ok(mut segments) = api_url.path_segments_mut() {
+            segments.pop_if_empty().extend(["api", "v4"]);
+        }
+        Self {
+            client: Client::new(),
+            api_url,
+            token,
+        }
+    }
+
+    /// Get the URL of `path` under the project with the ID or path (e.g. `group/project`) `project`
+    fn project_url<'a>(&self, project: &str, path: impl IntoIterator<Item = &'a str>) -> Url {
+        let mut url = self.api_url.clone();
+        if let Ok(mut segments) = url.path_segments_mut() {
+            // The project's path is percent-encoded into a single segment
+            segments.extend(["projects", project]).extend(path);
+        }
+        url
+    }
+
+    async fn get<T: DeserializeOwned>(&self, url: Url) -> Result<(T, reqwest::header::HeaderMap)> {
+        let mut request = self.client.get(url);
+        if let Some(token) = &self.token {
+            request = request.header("PRIVATE-TOKEN", token);
+        }
+        let response = request.send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(Error::NotFound);
+        }
+        let response = response.error_for_status()?;
+        let headers = response.headers().clone();
+        Ok((serde_json::from_slice(&response.bytes().await?)?, headers))
+    }
+
+    /// Get the headers to download the file at `url` with,
+    /// which authenticate with the token only if `url` is on this instance
+    ///
+    /// The token is sent in the `Authorization` header, which unlike `PRIVATE-TOKEN` isn't forwarded on redirects to other hosts.
+    pub fn download_headers(&self, url: &Url) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(token) = &self.token {
+            if url.host() == self.api_url.host()
+                && url.port_or_known_default() == self.api_url.port_or_known_default()
+            {
+                if let Ok(mut value) = HeaderValue::from_str(&format!("Bearer {token}")) {
+                    value.set_sensitive(true);
+                    headers.insert(AUTHORIZATION, value);
+                }
+            }
+        }
+        headers
+    }
+
+    /// Get the page `page` (starting from 1) of the releases of `project`, newest first
+    ///
+    /// Returns the releases and the number of the next page, if there is one.
+    pub async fn list_releases(
+        &self,
+        project: &str,
+        page: u32,
+    ) -> Result<(Vec<Release>, Option<u32>)> {
+        let mut url = self.project_url(project, ["releases"]);
+        url.query_pairs_mut()
+            .append_pair("per_page", "100")
+            .append_pair("page", &page.to_string());
+        let (releases, headers) = self.get(url).await?;
+        let next_page = headers
+            .get("x-next-page")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok());
+        Ok((releases, next_page))
+    }
+
+    /// Get the release of `project` with `tag`
+    pub async fn get_release(&self, project: &str, tag: &str) -> Result<Release> {
+        Ok(self
+            .get(self.project_url(project, ["releases", tag]))
+            .await?
+            .0)
+    }
+}
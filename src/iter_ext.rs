@@ -13,6 +13,15 @@ where
         self.collect::<std::collections::HashSet<T>>()
     }
 
+    /// Collects the elements of `self` into a `Vec`, leaving out repeated elements while keeping their order
+    fn collect_unique(self) -> Vec<T>
+    where
+        T: Eq + std::hash::Hash + Clone,
+    {
+        let mut seen = std::collections::HashSet::new();
+        self.filter(|e| seen.insert(e.clone())).collect_vec()
+    }
+
     /// Delimits elements of `self` with a comma and returns a single string
     fn display(self, sep: impl AsRef<str>) -> String
     where
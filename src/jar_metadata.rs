@@ -0,0 +1,705 @@
+use crate::{
+    config::structs::{Environment, ModLoader},
+    game_version::{self, Manifest},
+    read_wrapper,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fs::File,
+    io::{Cursor, Read, Seek},
+    path::Path,
+};
+use zip::{result::ZipError, ZipArchive};
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub enum Error {
+    IOError(#[from] std::io::Error),
+    ZipError(#[from] ZipError),
+    JSONError(#[from] serde_json::Error),
+    TOMLError(#[from] toml::de::Error),
+}
+type Result<T> = std::result::Result<T, Error>;
+
+/// The metadata of a mod, read from its descriptor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JarMetadata {
+    pub id: String,
+    pub version: String,
+    /// The display name of the mod
+    pub name: Option<String>,
+    /// The loaders that the descriptor is for
+    pub loaders: Vec<ModLoader>,
+    /// The game version requirements, in the format the descriptor uses
+    ///
+    /// For example, `>=1.20.1` for Fabric and Quilt, or `[1.20.1,1.21)` for Forge and NeoForge.
+    /// The mod supports a game version if it satisfies any of the requirements.
+    pub game_versions: Vec<String>,
+    /// The mods this mod requires
+    pub dependencies: Vec<JarDependency>,
+    /// The mods this mod is incompatible with
+    pub breaks: Vec<JarDependency>,
+    /// The side(s) of the game the mod runs on, if the descriptor declares it
+    pub environment: Option<Environment>,
+    /// The mods bundled inside this JAR file (i.e. jar-in-jar)
+    pub nested: Vec<JarMetadata>,
+}
+
+/// A dependency (or incompatibility) declared by a mod
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JarDependency {
+    pub id: String,
+    /// The version requirements, in the format the descriptor uses
+    ///
+    /// The dependency is satisfied if any of the requirements are. No requirements means any version.
+    pub versions: Vec<String>,
+}
+
+/// The IDs that refer to the game rather than a mod
+const GAME_IDS: [&str; 1] = ["minecraft"];
+/// The IDs that refer to Java or a loader rather than a mod
+const PLATFORM_IDS: [&str; 5] = ["java", "fabricloader", "quilt_loader", "forge", "neoforge"];
+
+/// Whether the dependency `id` refers to a mod rather than the game, Java, or a loader
+fn is_mod_id(id: &str) -> bool {
+    !GAME_IDS.contains(&id) && !PLATFORM_IDS.contains(&id)
+}
+
+/// Read the metadata of the mods in the JAR file at `path`
+pub fn read_jar(path: impl AsRef<Path>) -> Result<Vec<JarMetadata>> {
+    read_jar_from(File::open(path)?)
+}
+
+/// Read the metadata of the mods in the JAR file provided by `input`
+///
+/// Fabric (`fabric.mod.json`), Quilt (`quilt.mod.json`), Forge (`META-INF/mods.toml`),
+/// and NeoForge (`META-INF/neoforge.mods.toml`) descriptors are supported.
+/// A JAR file may contain more than one mod (e.g. a Forge descriptor listing several mods,
+/// or a JAR with descriptors for multiple loaders), so all of them are returned.
+/// Returns an empty list if the JAR file doesn't have a descriptor.
+pub fn read_jar_from(input: impl Read + Seek) -> Result<Vec<JarMetadata>> {
+    let mut jar = ZipArchive::new(input)?;
+    let mut mods = vec![];
+
+    if let Some(descriptor) = read_entry(&mut jar, "fabric.mod.json")? {
+        mods.push(parse_fabric(&descriptor, &mut jar)?);
+    }
+    if let Some(descriptor) = read_entry(&mut jar, "quilt.mod.json")? {
+        mods.push(parse_quilt(&descriptor, &mut jar)?);
+    }
+    for (name, loader) in [
+        ("META-INF/mods.toml", ModLoader::Forge),
+        ("META-INF/neoforge.mods.toml", ModLoader::NeoForge),
+    ] {
+        if let Some(descriptor) = read_entry(&mut jar, name)? {
+            mods.extend(parse_mods_toml(&descriptor, loader, &mut jar)?);
+        }
+    }
+
+    Ok(mods)
+}
+
+/// Returns the contents of the `name` entry in the `jar` if it exists
+fn read_entry(jar: &mut ZipArchive<impl Read + Seek>, name: &str) -> Result<Option<String>> {
+    match jar.by_name(name) {
+        Ok(entry) => Ok(Some(read_wrapper(entry)?)),
+        Err(ZipError::FileNotFound) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Read the mods in the nested JAR files at `paths` in the `jar`
+///
+/// Nested JAR files that are missing or invalid are skipped.
+fn read_nested<'a>(
+    jar: &mut ZipArchive<impl Read + Seek>,
+    paths: impl IntoIterator<Item = &'a str>,
+) -> Vec<JarMetadata> {
+    let mut nested = vec![];
+    for path in paths {
+        let mut bytes = vec![];
+        if jar
+            .by_name(path)
+            .is_ok_and(|mut entry| entry.read_to_end(&mut bytes).is_ok())
+        {
+            if let Ok(mods) = read_jar_from(Cursor::new(bytes)) {
+                nested.extend(mods);
+            }
+        }
+    }
+    nested
+}
+
+/// Get the alternative requirements in a JSON value, which can be a string, an array of alternatives,
+/// or an object with `any` alternatives or `all` requirements (as used by Quilt)
+///
+/// Requirements that all have to be met are joined with spaces (e.g. `>=1.20 <1.21`).
+fn strings_in(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(string) => vec![string.clone()],
+        Value::Array(values) => values.iter().flat_map(strings_in).collect(),
+        Value::Object(map) => map
+            .iter()
+            .flat_map(|(key, value)| match (key.as_str(), value) {
+                ("all", Value::Array(values)) => {
+                    values.iter().fold(vec![String::new()], |combined, value| {
+                        let alternatives = strings_in(value);
+                        combined
+                            .iter()
+                            .flat_map(|c| alternatives.iter().map(move |a| format!("{c} {a}")))
+                            .collect()
+                    })
+                }
+                _ => strings_in(value),
+            })
+            .map(|requirement| requirement.trim().to_owned())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+#[derive(Deserialize)]
+struct FabricModJson {
+    id: String,
+    version: String,
+    name: Option<String>,
+    environment: Option<String>,
+    #[serde(default)]
+    depends: HashMap<String, Value>,
+    #[serde(default)]
+    breaks: HashMap<String, Value>,
+    #[serde(default)]
+    jars: Vec<FabricJar>,
+}
+
+#[derive(Deserialize)]
+struct FabricJar {
+    file: String,
+}
+
+fn parse_fabric(descriptor: &str, jar: &mut ZipArchive<impl Read + Seek>) -> Result<JarMetadata> {
+    let descriptor: FabricModJson = serde_json::from_str(descriptor)?;
+    let dependencies = |map: &HashMap<String, Value>| {
+        map.iter()
+            .filter(|(id, _)| is_mod_id(id))
+            .map(|(id, versions)| JarDependency {
+                id: id.clone(),
+                versions: strings_in(versions),
+            })
+            .collect()
+    };
+
+    Ok(JarMetadata {
+        game_versions: GAME_IDS
+            .iter()
+            .filter_map(|id| descriptor.depends.get(*id))
+            .flat_map(strings_in)
+            .collect(),
+        dependencies: dependencies(&descriptor.depends),
+        breaks: dependencies(&descriptor.breaks),
+        environment: match descriptor.environment.as_deref() {
+            Some("client") => Some(Environment::Client),
+            Some("server") => Some(Environment::Server),
+            _ => Some(Environment::Both),
+        },
+        nested: read_nested(jar, descriptor.jars.iter().map(|j| j.file.as_str())),
+        id: descriptor.id,
+        version: descriptor.version,
+        name: descriptor.name,
+        loaders: vec![ModLoader::Fabric],
+    })
+}
+
+#[derive(Deserialize)]
+struct QuiltModJson {
+    quilt_loader: QuiltLoader,
+    minecraft: Option<QuiltMinecraft>,
+}
+
+#[derive(Deserialize)]
+struct QuiltLoader {
+    id: String,
+    version: String,
+    metadata: Option<QuiltMetadata>,
+    #[serde(default)]
+    depends: Vec<QuiltDependency>,
+    #[serde(default)]
+    breaks: Vec<QuiltDependency>,
+    #[serde(default)]
+    jars: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct QuiltMetadata {
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum QuiltDependency {
+    Id(String),
+    Object {
+        id: String,
+        #[serde(default)]
+        versions: Value,
+        #[serde(default)]
+        optional: bool,
+    },
+    /// Any one of the dependencies is enough
+    Any(Vec<QuiltDependency>),
+}
+
+impl QuiltDependency {
+    /// The dependency, unless it's optional or a choice between several dependencies
+    fn into_dependency(self) -> Option<JarDependency> {
+        match self {
+            Self::Id(id) => Some(JarDependency {
+                id,
+                versions: vec![],
+            }),
+            Self::Object {
+                id,
+                versions,
+                optional: false,
+            } => Some(JarDependency {
+                id,
+                versions: strings_in(&versions),
+            }),
+            Self::Object { optional: true, .. } | Self::Any(_) => None,
+        }
+    }
+
+    /// The game version requirements in this dependency, including in each of the choices
+    fn game_versions(&self) -> Vec<String> {
+        match self {
+            Self::Object { id, versions, .. } if GAME_IDS.contains(&id.as_str()) => {
+                strings_in(versions)
+            }
+            Self::Any(choices) => choices.iter().flat_map(Self::game_versions).collect(),
+            _ => vec![],
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct QuiltMinecraft {
+    environment: Option<String>,
+}
+
+fn parse_quilt(descriptor: &str, jar: &mut ZipArchive<impl Read + Seek>) -> Result<JarMetadata> {
+    let descriptor: QuiltModJson = serde_json::from_str(descriptor)?;
+    let loader = descriptor.quilt_loader;
+    // Dependencies that are a choice between several mods can't be represented, so they're left out
+    let dependencies = |list: Vec<QuiltDependency>| {
+        list.into_iter()
+            .filter_map(QuiltDependency::into_dependency)
+            .filter(|d| is_mod_id(&d.id))
+            .collect()
+    };
+
+    Ok(JarMetadata {
+        game_versions: loader
+            .depends
+            .iter()
+            .flat_map(QuiltDependency::game_versions)
+            .collect(),
+        dependencies: dependencies(loader.depends),
+        breaks: dependencies(loader.breaks),
+        environment: match descriptor
+            .minecraft
+            .and_then(|minecraft| minecraft.environment)
+            .as_deref()
+        {
+            Some("client") => Some(Environment::Client),
+            Some("dedicated_server") => Some(Environment::Server),
+            _ => Some(Environment::Both),
+        },
+        nested: read_nested(jar, loader.jars.iter().map(String::as_str)),
+        id: loader.id,
+        version: loader.version,
+        name: loader.metadata.and_then(|metadata| metadata.name),
+        loaders: vec![ModLoader::Quilt],
+    })
+}
+
+#[derive(Deserialize)]
+struct ModsToml {
+    #[serde(default)]
+    mods: Vec<ModsTomlMod>,
+    #[serde(default)]
+    dependencies: HashMap<String, Vec<ModsTomlDependency>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModsTomlMod {
+    mod_id: String,
+    version: Option<String>,
+    display_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModsTomlDependency {
+    mod_id: String,
+    /// Used by Forge
+    mandatory: Option<bool>,
+    /// Used by NeoForge (`required`, `optional`, `incompatible`, or `discouraged`)
+    #[serde(rename = "type")]
+    type_: Option<String>,
+    version_range: Option<String>,
+}
+
+impl ModsTomlDependency {
+    /// Whether the dependency is required, which is NeoForge's default if neither field is set
+    fn is_required(&self) -> bool {
+        match (&self.type_, self.mandatory) {
+            (Some(type_), _) => type_ == "required",
+            (None, Some(mandatory)) => mandatory,
+            (None, None) => true,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JarJarMetadata {
+    jars: Vec<JarJarEntry>,
+}
+
+#[derive(Deserialize)]
+struct JarJarEntry {
+    path: String,
+}
+
+fn parse_mods_toml(
+    descriptor: &str,
+    loader: ModLoader,
+    jar: &mut ZipArchive<impl Read + Seek>,
+) -> Result<Vec<JarMetadata>> {
+    let descriptor: ModsToml = toml::from_str(descriptor)?;
+
+    // The version is usually substituted in from the JAR manifest
+    let jar_version = read_entry(jar, "META-INF/MANIFEST.MF")?.and_then(|manifest| {
+        manifest.lines().find_map(|line| {
+            line.strip_prefix("Implementation-Version:")
+                .map(|v| v.trim().to_owned())
+        })
+    });
+    let nested = match read_entry(jar, "META-INF/jarjar/metadata.json")? {
+        Some(metadata) => {
+            let metadata: JarJarMetadata = serde_json::from_str(&metadata)?;
+            read_nested(jar, metadata.jars.iter().map(|j| j.path.as_str()))
+        }
+        None => vec![],
+    };
+
+    Ok(descriptor
+        .mods
+        .into_iter()
+        .map(|mod_| {
+            let declared = descriptor
+                .dependencies
+                .get(&mod_.mod_id)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let to_dependency = |d: &ModsTomlDependency| JarDependency {
+                id: d.mod_id.clone(),
+                versions: d.version_range.iter().cloned().collect(),
+            };
+            JarMetadata {
+                game_versions: declared
+                    .iter()
+                    .filter(|d| GAME_IDS.contains(&d.mod_id.as_str()))
+                    .filter_map(|d| d.version_range.clone())
+                    .collect(),
+                dependencies: declared
+                    .iter()
+                    .filter(|d| is_mod_id(&d.mod_id) && d.is_required())
+                    .map(to_dependency)
+                    .collect(),
+                breaks: declared
+                    .iter()
+                    .filter(|d| is_mod_id(&d.mod_id) && d.type_.as_deref() == Some("incompatible"))
+                    .map(to_dependency)
+                    .collect(),
+                environment: None,
+                nested: nested.clone(),
+                version: match mod_.version {
+                    Some(version) if !version.starts_with("${") => version,
+                    _ => jar_version.clone().unwrap_or_default(),
+                },
+                id: mod_.mod_id,
+                name: mod_.display_name,
+                loaders: vec![loader.clone()],
+            }
+        })
+        .collect())
+}
+
+/// Returns whether the game `version` satisfies a `requirement` declared in a descriptor
+///
+/// Supports Maven version ranges used by Forge and NeoForge (e.g. `[1.20.1,1.21)`),
+/// and the version predicates used by Fabric and Quilt (e.g. `>=1.20.1 <1.21`, `~1.20`, `1.20.x`, or `*`).
+/// Versions that can't be compared with the requirement don't satisfy it.
+pub fn requirement_matches(requirement: &str, version: &str, manifest: Option<&Manifest>) -> bool {
+    let requirement = requirement.trim();
+    if requirement.starts_with(['[', '(']) {
+        maven_range_matches(requirement, version, manifest)
+    } else {
+        requirement
+            .split_whitespace()
+            .all(|predicate| predicate_matches(predicate, version, manifest))
+    }
+}
+
+/// Check a Maven version range, which may be a union of ranges (e.g. `[1.19,1.20),[1.20.1]`)
+fn maven_range_matches(ranges: &str, version: &str, manifest: Option<&Manifest>) -> bool {
+    let cmp = |bound: &str| game_version::compare(version, bound.trim(), manifest);
+    ranges
+        .split_inclusive([']', ')'])
+        .map(|range| range.trim_start_matches([',', ' ']))
+        .filter(|range| !range.is_empty())
+        .any(|range| {
+            let inclusive_start = range.starts_with('[');
+            let inclusive_end = range.ends_with(']');
+            let inner = &range[1..range.len() - 1];
+            match inner.split_once(',') {
+                None => cmp(inner).is_some_and(Ordering::is_eq),
+                Some((start, end)) => {
+                    (start.trim().is_empty()
+                        || cmp(start).is_some_and(|o| o.is_gt() || (inclusive_start && o.is_eq())))
+                        && (end.trim().is_empty()
+                            || cmp(end).is_some_and(|o| o.is_lt() || (inclusive_end && o.is_eq())))
+                }
+            }
+        })
+}
+
+/// Check a single Fabric or Quilt version predicate
+fn predicate_matches(predicate: &str, version: &str, manifest: Option<&Manifest>) -> bool {
+    let cmp = |bound: &str| game_version::compare(version, bound, manifest);
+    // Get the exclusive upper bound when the number at `index` is incremented (e.g. `1.21` for `~1.20.1`)
+    let upper = |bound: &str, index: usize| {
+        let mut numbers = bound
+            .split('.')
+            .map_while(|n| n.parse::<u32>().ok())
+            .take(index + 1)
+            .collect::<Vec<_>>();
+        numbers.resize(index + 1, 0);
+        numbers[index] += 1;
+        numbers
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(".")
+    };
+
+    if predicate == "*" {
+        true
+    } else if let Some(bound) = predicate.strip_prefix(">=") {
+        cmp(bound).is_some_and(Ordering::is_ge)
+    } else if let Some(bound) = predicate.strip_prefix("<=") {
+        cmp(bound).is_some_and(Ordering::is_le)
+    } else if let Some(bound) = predicate.strip_prefix('>') {
+        cmp(bound).is_some_and(Ordering::is_gt)
+    } else if let Some(bound) = predicate.strip_prefix('<') {
+        cmp(bound).is_some_and(Ordering::is_lt)
+    } else if let Some(bound) = predicate.strip_prefix('~') {
+        cmp(bound).is_some_and(Ordering::is_ge)
+            && cmp(&upper(bound, 1)).is_some_and(Ordering::is_lt)
+    } else if let Some(bound) = predicate.strip_prefix('^') {
+        cmp(bound).is_some_and(Ordering::is_ge)
+            && cmp(&upper(bound, 0)).is_some_and(Ordering::is_lt)
+    } else {
+        let predicate = predicate.strip_prefix('=').unwrap_or(predicate);
+        if let Some(line) = predicate
+            .strip_suffix(".x")
+            .or_else(|| predicate.strip_suffix(".*"))
+        {
+            // Development versions are in the line of the release they lead up to
+            game_version::target_release(version, manifest).is_some_and(|release| {
+                release == line
+                    || release
+                        .strip_prefix(line)
+                        .is_some_and(|rest| rest.starts_with('.'))
+            })
+        } else {
+            cmp(predicate).is_some_and(Ordering::is_eq)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write as _;
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    /// Build a JAR file containing the `entries`
+    fn jar(entries: &[(&str, &str)]) -> Cursor<Vec<u8>> {
+        let mut jar = ZipWriter::new(Cursor::new(vec![]));
+        for (name, contents) in entries {
+            jar.start_file(*name, SimpleFileOptions::default()).unwrap();
+            jar.write_all(contents.as_bytes()).unwrap();
+        }
+        jar.finish().unwrap()
+    }
+
+    fn ids(dependencies: &[JarDependency]) -> Vec<&str> {
+        let mut ids = dependencies
+            .iter()
+            .map(|d| d.id.as_str())
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn reads_fabric_descriptor() {
+        let mods = read_jar_from(jar(&[(
+            "fabric.mod.json",
+            include_str!("../tests/fixtures/fabric.mod.json"),
+        )]))
+        .unwrap();
+        assert_eq!(mods.len(), 1);
+        let sodium = &mods[0];
+
+        assert_eq!(sodium.id, "sodium");
+        assert_eq!(sodium.version, "0.5.11+mc1.20.1");
+        assert_eq!(sodium.name.as_deref(), Some("Sodium"));
+        assert_eq!(sodium.loaders, [ModLoader::Fabric]);
+        assert_eq!(sodium.game_versions, ["1.20", "1.20.1"]);
+        assert_eq!(sodium.environment, Some(Environment::Client));
+        assert_eq!(
+            ids(&sodium.dependencies),
+            [
+                "fabric-block-view-api-v2",
+                "fabric-renderer-api-v1",
+                "fabric-rendering-data-attachment-v1",
+                "fabric-rendering-fluids-v1",
+                "fabric-resource-loader-v0",
+            ]
+        );
+        assert_eq!(ids(&sodium.breaks), ["canvas", "iris", "optifabric"]);
+        let iris = sodium.breaks.iter().find(|d| d.id == "iris").unwrap();
+        assert_eq!(iris.versions, ["<1.6.0"]);
+        // The nested JAR file isn't in the test JAR
+        assert!(sodium.nested.is_empty());
+    }
+
+    #[test]
+    fn reads_quilt_descriptor() {
+        let mods = read_jar_from(jar(&[(
+            "quilt.mod.json",
+            include_str!("../tests/fixtures/quilt.mod.json"),
+        )]))
+        .unwrap();
+        assert_eq!(mods.len(), 1);
+        let qfapi = &mods[0];
+
+        assert_eq!(qfapi.id, "quilted_fabric_api");
+        assert_eq!(qfapi.version, "7.5.0+0.91.0-1.20.1");
+        assert_eq!(qfapi.name.as_deref(), Some("Quilted Fabric API"));
+        assert_eq!(qfapi.loaders, [ModLoader::Quilt]);
+        assert_eq!(qfapi.game_versions, ["=1.20.1"]);
+        assert_eq!(qfapi.environment, Some(Environment::Both));
+        // Optional dependencies and choices between dependencies are left out
+        assert_eq!(ids(&qfapi.dependencies), ["quilt_base"]);
+        assert_eq!(ids(&qfapi.breaks), ["fabric-api"]);
+    }
+
+    #[test]
+    fn reads_quilt_version_requirements() {
+        let mods = read_jar_from(jar(&[(
+            "quilt.mod.json",
+            include_str!("../tests/fixtures/quilt_requirements.mod.json"),
+        )]))
+        .unwrap();
+        let example = &mods[0];
+
+        assert_eq!(example.game_versions, [">=1.20 <1.21"]);
+        let supports = |version| {
+            example
+                .game_versions
+                .iter()
+                .any(|r| requirement_matches(r, version, None))
+        };
+        assert!(supports("1.20.4"));
+        assert!(!supports("1.21"));
+        assert!(!supports("1.19.4"));
+
+        let qsl = &example.dependencies[0];
+        assert_eq!(qsl.id, "qsl");
+        assert_eq!(qsl.versions, [">=6.1.0 <7", "=8.0.0"]);
+    }
+
+    #[test]
+    fn reads_forge_descriptor() {
+        let mods = read_jar_from(jar(&[
+            (
+                "META-INF/mods.toml",
+                include_str!("../tests/fixtures/mods.toml"),
+            ),
+            (
+                "META-INF/MANIFEST.MF",
+                "Manifest-Version: 1.0\nImplementation-Version: 0.5.1.f\n",
+            ),
+        ]))
+        .unwrap();
+        assert_eq!(mods.len(), 1);
+        let create = &mods[0];
+
+        assert_eq!(create.id, "create");
+        // The version is substituted in from the JAR manifest
+        assert_eq!(create.version, "0.5.1.f");
+        assert_eq!(create.name.as_deref(), Some("Create"));
+        assert_eq!(create.loaders, [ModLoader::Forge]);
+        assert_eq!(create.game_versions, ["[1.20.1]"]);
+        assert_eq!(create.environment, None);
+        assert_eq!(ids(&create.dependencies), ["flywheel"]);
+        assert_eq!(create.dependencies[0].versions, ["[0.6.10,0.6.11)"]);
+        assert!(create.breaks.is_empty());
+    }
+
+    #[test]
+    fn reads_neoforge_descriptor() {
+        let mods = read_jar_from(jar(&[(
+            "META-INF/neoforge.mods.toml",
+            include_str!("../tests/fixtures/neoforge.mods.toml"),
+        )]))
+        .unwrap();
+        assert_eq!(mods.len(), 1);
+        let jei = &mods[0];
+
+        assert_eq!(jei.id, "jei");
+        assert_eq!(jei.version, "19.21.0.247");
+        assert_eq!(jei.loaders, [ModLoader::NeoForge]);
+        assert_eq!(jei.game_versions, ["[1.21.1,1.21.2)"]);
+        // Dependencies without a type are required
+        assert_eq!(ids(&jei.dependencies), ["architectury"]);
+        assert_eq!(ids(&jei.breaks), ["roughlyenoughitems"]);
+    }
+
+    #[test]
+    fn reads_jar_without_descriptor() {
+        let mods = read_jar_from(jar(&[("com/example/Library.class", "")])).unwrap();
+        assert!(mods.is_empty());
+    }
+
+    #[test]
+    fn matches_requirements() {
+        assert!(requirement_matches("[1.20.1,1.21)", "1.20.4", None));
+        assert!(!requirement_matches("[1.20.1,1.21)", "1.21", None));
+        assert!(requirement_matches("[1.19,1.20),[1.20.1]", "1.20.1", None));
+        assert!(!requirement_matches("[1.19,1.20),[1.20.1]", "1.20", None));
+        assert!(requirement_matches(">=1.20.1 <1.21", "1.20.6", None));
+        assert!(requirement_matches("~1.20", "1.20.6", None));
+        assert!(!requirement_matches("~1.20", "1.21", None));
+        assert!(requirement_matches("1.20.x", "1.20.2", None));
+        assert!(requirement_matches("*", "1.8.9", None));
+        assert!(!requirement_matches("=1.20.1", "1.20", None));
+    }
+}
@@ -1,6 +1,10 @@
 pub mod add;
 pub mod config;
+pub mod game_version;
+pub mod gitlab;
 pub mod iter_ext;
+pub mod jar_metadata;
+pub mod mod_version;
 pub mod modpack;
 pub mod scan;
 pub mod upgrade;
@@ -19,6 +23,13 @@ pub static GITHUB_API: LazyLock<octocrab::Octocrab> = LazyLock::new(|| {
     github.build().expect("Could not build GitHub client")
 });
 
+pub static GITLAB_API: LazyLock<gitlab::GitLab> = LazyLock::new(|| {
+    gitlab::GitLab::new(
+        &"https://gitlab.com".parse().expect("Invalid GitLab URL"),
+        std::env::var("GITLAB_TOKEN").ok(),
+    )
+});
+
 pub static CURSEFORGE_API: LazyLock<furse::Furse> = LazyLock::new(|| {
     furse::Furse::new(&std::env::var("CURSEFORGE_API_KEY").unwrap_or(String::from(
         "$2a$10$sI.yRk4h4R49XYF94IIijOrO4i3W3dAFZ4ssOlNE10GYrDhc2j8K.",
@@ -0,0 +1,411 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// A leniently parsed mod version number
+///
+/// Mod versions are messy (e.g. `mc1.20.1-0.5.1.f`, `v2.3.0+1.21`, or `Create 0.5.1.f`),
+/// so the first dot-separated number that isn't a game version is used.
+/// Suffixes such as `.f` are kept and compared alphabetically, and versions marked as
+/// alpha, beta, pre-release, or release candidate are ordered before the corresponding release.
+#[derive(Debug, Clone)]
+pub struct ModVersion {
+    pub numbers: Vec<u64>,
+    /// Letters at the end of the version number (e.g. `.f` in `0.5.1.f`)
+    pub suffix: String,
+    /// The pre-release identifier (e.g. `beta.3` in `2.0.0-beta.3`)
+    pub pre_release: Option<String>,
+}
+
+const PRE_RELEASE_MARKERS: [&str; 5] = ["alpha", "beta", "pre", "rc", "snapshot"];
+
+impl ModVersion {
+    /// Parse the version number in `from`, ignoring tokens that are one of `game_versions`
+    ///
+    /// Returns `None` if no version number could be found.
+    pub fn parse(from: &str, game_versions: &[String]) -> Option<Self> {
+        let tokens = from
+            .split(['-', '+', '_', ' '])
+            .filter(|token| !token.is_empty())
+            .collect::<Vec<_>>();
+
+        let (position, version) = tokens.iter().enumerate().find_map(|(i, token)| {
+            let token = token.strip_prefix(['v', 'V']).unwrap_or(token);
+            // Skip game versions (e.g. `1.20.1` or `mc1.20.1`)
+            if token.starts_with("mc")
+                || token.starts_with("MC")
+                || game_versions.iter().any(|v| v == token)
+            {
+                return None;
+            }
+            Self::parse_token(token).map(|version| (i, version))
+        })?;
+
+        let pre_release = tokens[position + 1..]
+            .iter()
+            .map(|token| token.to_lowercase())
+            .find(|token| is_pre_release(token));
+
+        Some(Self {
+            pre_release,
+            ..version
+        })
+    }
+
+    /// Parse a single token such as `0.5.1.f` or `2.3.0`
+    pub(crate) fn parse_token(token: &str) -> Option<Self> {
+        let mut numbers = vec![];
+        let mut suffix = String::new();
+        for part in token.split('.') {
+            let digits = part.len() - part.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            if digits == 0 || !suffix.is_empty() {
+                // The numeric part has ended
+                if numbers.is_empty() {
+                    return None;
+                }
+                suffix.push('.');
+                suffix.push_str(part);
+            } else {
+                numbers.push(part[..digits].parse().ok()?);
+                suffix.push_str(&part[digits..]);
+            }
+        }
+
+        if numbers.is_empty() {
+            None
+        } else {
+            Some(Self {
+                numbers,
+                suffix: suffix.to_lowercase(),
+                pre_release: None,
+            })
+        }
+    }
+}
+
+/// Whether `token` starts with a pre-release marker that isn't part of a longer word,
+/// e.g. `beta`, `rc1`, or `pre.2`, but not `preview`
+fn is_pre_release(token: &str) -> bool {
+    PRE_RELEASE_MARKERS.iter().any(|marker| {
+        token
+            .strip_prefix(marker)
+            .is_some_and(|rest| !rest.starts_with(|c: char| c.is_ascii_alphabetic()))
+    })
+}
+
+/// An identifier in a pre-release, numeric identifiers are ordered before alphanumeric ones
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Identifier<'a> {
+    Numeric(u64),
+    Alphanumeric(&'a str),
+}
+
+/// Split `pre_release` into its dot-separated identifiers,
+/// also splitting letters from the numbers after them (e.g. `beta10` is `beta` and `10`)
+fn identifiers(pre_release: &str) -> Vec<Identifier<'_>> {
+    let mut identifiers = vec![];
+    for part in pre_release.split('.').filter(|part| !part.is_empty()) {
+        let letters = part.trim_end_matches(|c: char| c.is_ascii_digit());
+        let digits = &part[letters.len()..];
+        match (letters.is_empty(), digits.parse()) {
+            (true, Ok(number)) => identifiers.push(Identifier::Numeric(number)),
+            (false, Ok(number)) => {
+                identifiers.push(Identifier::Alphanumeric(letters));
+                identifiers.push(Identifier::Numeric(number));
+            }
+            _ => identifiers.push(Identifier::Alphanumeric(part)),
+        }
+    }
+    identifiers
+}
+
+impl Ord for ModVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Pad the shorter version with zeroes, since `1.2` is the same as `1.2.0`
+        let len = self.numbers.len().max(other.numbers.len());
+        let pad = |n: &Vec<u64>| {
+            n.iter()
+                .copied()
+                .chain(std::iter::repeat(0))
+                .take(len)
+                .collect::<Vec<_>>()
+        };
+        pad(&self.numbers)
+            .cmp(&pad(&other.numbers))
+            .then_with(|| match (&self.pre_release, &other.pre_release) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                // Compare the identifiers like SemVer, a shorter pre-release is ordered first if it is a prefix
+                (Some(a), Some(b)) => identifiers(a).cmp(&identifiers(b)),
+            })
+            .then_with(|| self.suffix.cmp(&other.suffix))
+    }
+}
+
+impl PartialEq for ModVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for ModVersion {}
+
+impl PartialOrd for ModVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for ModVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let numbers = self
+            .numbers
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        write!(f, "{}{}", numbers.join("."), self.suffix)?;
+        if let Some(pre_release) = &self.pre_release {
+            write!(f, "-{pre_release}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReqOperator {
+    Less,
+    LessEqual,
+    Equal,
+    GreaterEqual,
+    Greater,
+    /// Allows updates that don't change the leftmost non-zero number (e.g. `^0.5` allows `0.5.x`)
+    Caret,
+    /// Allows patch updates (e.g. `~0.5.1` allows `0.5.x` where x >= 1)
+    Tilde,
+    /// Allows any numbers in place of the wildcard (e.g. `0.5.x` or `0.5.*`)
+    Wildcard,
+}
+
+/// A comparison against a mod version, e.g. `^0.5`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReqComparator {
+    pub operator: ReqOperator,
+    pub version: ModVersion,
+}
+
+/// A requirement on a mod's version, written as comparators separated by commas or spaces (e.g. `>=0.5, <2.0`)
+///
+/// A version without an operator has to match exactly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModVersionReq(pub Vec<ReqComparator>);
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error("{0:?} is not a valid mod version requirement")]
+pub struct ReqParseError(pub String);
+
+impl FromStr for ModVersionReq {
+    type Err = ReqParseError;
+
+    fn from_str(from: &str) -> Result<Self, Self::Err> {
+        let comparators = from
+            .split([',', ' '])
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                let (mut operator, version) = if let Some(v) = s.strip_prefix(">=") {
+                    (ReqOperator::GreaterEqual, v)
+                } else if let Some(v) = s.strip_prefix("<=") {
+                    (ReqOperator::LessEqual, v)
+                } else if let Some(v) = s.strip_prefix('>') {
+                    (ReqOperator::Greater, v)
+                } else if let Some(v) = s.strip_prefix('<') {
+                    (ReqOperator::Less, v)
+                } else if let Some(v) = s.strip_prefix('^') {
+                    (ReqOperator::Caret, v)
+                } else if let Some(v) = s.strip_prefix('~') {
+                    (ReqOperator::Tilde, v)
+                } else {
+                    (ReqOperator::Equal, s.strip_prefix('=').unwrap_or(s))
+                };
+                let version = if let Some(v) = version
+                    .strip_suffix(".x")
+                    .or_else(|| version.strip_suffix(".*"))
+                {
+                    if operator != ReqOperator::Equal {
+                        return Err(ReqParseError(from.to_owned()));
+                    }
+                    operator = ReqOperator::Wildcard;
+                    v
+                } else {
+                    version
+                };
+                ModVersion::parse_token(version.strip_prefix(['v', 'V']).unwrap_or(version))
+                    .map(|version| ReqComparator { operator, version })
+                    .ok_or_else(|| ReqParseError(from.to_owned()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if comparators.is_empty() {
+            Err(ReqParseError(from.to_owned()))
+        } else {
+            Ok(Self(comparators))
+        }
+    }
+}
+
+impl Display for ModVersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, comparator) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            let (prefix, suffix) = match comparator.operator {
+                ReqOperator::Less => ("<", ""),
+                ReqOperator::LessEqual => ("<=", ""),
+                ReqOperator::Equal => ("=", ""),
+                ReqOperator::GreaterEqual => (">=", ""),
+                ReqOperator::Greater => (">", ""),
+                ReqOperator::Caret => ("^", ""),
+                ReqOperator::Tilde => ("~", ""),
+                ReqOperator::Wildcard => ("", ".x"),
+            };
+            write!(f, "{prefix}{}{suffix}", comparator.version)?;
+        }
+        Ok(())
+    }
+}
+
+impl ReqComparator {
+    /// Get the exclusive upper bound of a caret, tilde, or wildcard comparator
+    fn upper_bound(&self) -> ModVersion {
+        let numbers = &self.version.numbers;
+        // The index of the number to increment
+        let index = match self.operator {
+            ReqOperator::Caret => numbers
+                .iter()
+                .position(|n| *n != 0)
+                .unwrap_or(numbers.len() - 1),
+            ReqOperator::Tilde => (numbers.len() - 1).min(1),
+            _ => numbers.len() - 1,
+        };
+        let mut numbers = numbers[..=index].to_vec();
+        numbers[index] += 1;
+        ModVersion {
+            numbers,
+            suffix: String::new(),
+            // Pre-releases are ordered before any other pre-release identifier,
+            // so that pre-releases of the upper bound are excluded
+            pre_release: Some(String::new()),
+        }
+    }
+
+    pub fn matches(&self, version: &ModVersion) -> bool {
+        match self.operator {
+            ReqOperator::Less => version < &self.version,
+            ReqOperator::LessEqual => version <= &self.version,
+            ReqOperator::Equal => version == &self.version,
+            ReqOperator::GreaterEqual => version >= &self.version,
+            ReqOperator::Greater => version > &self.version,
+            ReqOperator::Caret | ReqOperator::Tilde | ReqOperator::Wildcard => {
+                let lower = if self.operator == ReqOperator::Wildcard {
+                    // Wildcards also allow pre-releases of the version
+                    ModVersion {
+                        pre_release: Some(String::new()),
+                        ..self.version.clone()
+                    }
+                } else {
+                    self.version.clone()
+                };
+                version >= &lower && version < &self.upper_bound()
+            }
+        }
+    }
+}
+
+impl ModVersionReq {
+    /// Returns whether `version` satisfies all the comparators
+    pub fn matches(&self, version: &ModVersion) -> bool {
+        self.0.iter().all(|comparator| comparator.matches(version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(from: &str) -> ModVersion {
+        ModVersion::parse(from, &["1.20.1".to_owned()]).unwrap()
+    }
+
+    #[test]
+    fn parses_messy_versions() {
+        assert_eq!(version("mc1.20.1-0.5.1.f").to_string(), "0.5.1.f");
+        assert_eq!(version("v2.3.0+1.20.1").to_string(), "2.3.0");
+        assert_eq!(version("Create 0.5.1.f").to_string(), "0.5.1.f");
+        assert_eq!(version("sodium-fabric-1.20.1-0.5.3").to_string(), "0.5.3");
+        assert!(ModVersion::parse("release", &[]).is_none());
+    }
+
+    #[test]
+    fn detects_pre_release_markers() {
+        assert_eq!(
+            version("2.0.0-beta.3").pre_release.as_deref(),
+            Some("beta.3")
+        );
+        assert_eq!(version("2.0.0-RC1").pre_release.as_deref(), Some("rc1"));
+        assert_eq!(version("2.0.0-pre").pre_release.as_deref(), Some("pre"));
+        // Markers have to end at a number, a dot, or the end of the token
+        assert_eq!(version("2.0.0-preview").pre_release, None);
+        assert_eq!(version("2.0.0-alphabet").pre_release, None);
+        assert_eq!(version("2.0.0-release").pre_release, None);
+    }
+
+    #[test]
+    fn orders_numbers() {
+        assert!(version("1.2") == version("1.2.0"));
+        assert!(version("1.10.0") > version("1.9.9"));
+        assert!(version("0.5.1.f") > version("0.5.1.e"));
+        assert!(version("0.5.1.a") > version("0.5.1"));
+    }
+
+    #[test]
+    fn orders_pre_releases() {
+        let ordered = [
+            "2.0.0-alpha",
+            "2.0.0-alpha.1",
+            "2.0.0-alpha.beta",
+            "2.0.0-beta",
+            "2.0.0-beta.2",
+            "2.0.0-beta.11",
+            "2.0.0-rc.1",
+            "2.0.0",
+            "2.0.1-beta9",
+            "2.0.1-beta10",
+            "2.0.1",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(
+                version(pair[0]) < version(pair[1]),
+                "{} < {}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn matches_requirements() {
+        let req = |from: &str| from.parse::<ModVersionReq>().unwrap();
+        assert!(req("^0.5").matches(&version("0.5.9")));
+        assert!(!req("^0.5").matches(&version("0.6.0")));
+        assert!(!req("^0.5").matches(&version("0.6.0-beta.1")));
+        assert!(req("~0.5.1").matches(&version("0.5.4")));
+        assert!(!req("~0.5.1").matches(&version("0.5.0")));
+        assert!(req("0.5.x").matches(&version("0.5.0-rc.1")));
+        assert!(req(">=1.2, <2.0").matches(&version("1.9.0")));
+        assert!(!req(">=1.2, <2.0").matches(&version("2.0.0")));
+        assert!("1.2, ^".parse::<ModVersionReq>().is_err());
+    }
+}
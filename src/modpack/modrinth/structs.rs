@@ -1,3 +1,4 @@
+use crate::config::structs::Environment;
 use ferinth::structures::{project::ProjectSupportRange, version::Hash, Int};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, path::PathBuf};
@@ -50,8 +51,14 @@ pub struct ModpackFile {
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ModpackFileEnvironment {
-    client: ProjectSupportRange,
-    server: ProjectSupportRange,
+    pub client: ProjectSupportRange,
+    pub server: ProjectSupportRange,
+}
+
+impl ModpackFileEnvironment {
+    pub fn environment(&self) -> Option<Environment> {
+        Environment::from_support(&self.client, &self.server)
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
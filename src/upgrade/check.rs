@@ -1,94 +1,360 @@
-use super::Metadata;
+use super::{AssetKind, Metadata};
 use crate::{
-    config::filters::{Filter, ReleaseChannel},
+    config::{
+        compatibility::{self, scope_applies},
+        context::Context,
+        filters::{FileOrder, Filter, ReleaseChannel},
+        structs::{Environment, ModLoader},
+    },
+    game_version::{self, GameVersionRange, Manifest, RangeParseError, SymbolicVersion},
     iter_ext::{IterExt, IterExtPositions},
-    MODRINTH_API,
+    mod_version::{ModVersion, ModVersionReq, ReqParseError},
 };
+use chrono::{DateTime, TimeDelta, Utc};
 use ferinth::structures::tag::GameVersionType;
+use futures_util::future::BoxFuture;
 use regex::Regex;
-use std::{collections::HashSet, sync::OnceLock};
+use std::{cmp::Reverse, collections::HashSet, sync::Arc};
 
 #[derive(thiserror::Error, Debug)]
 #[error(transparent)]
 pub enum Error {
-    VersionGrouping(#[from] ferinth::Error),
     FilenameRegex(#[from] regex::Error),
+    VersionRange(#[from] RangeParseError),
+    VersionRequirement(#[from] ReqParseError),
     #[error("The following filter(s) were empty: {}", _0.iter().display(", "))]
     FilterEmpty(Vec<String>),
+    /// Use [`explain`] to find out which combination of filters caused this
     #[error("Failed to find a compatible combination")]
     IntersectFailure,
+    #[error("Only auxiliary files (e.g. sources JARs or checksums) are compatible, use a filename filter to select them")]
+    OnlyAuxiliary,
 }
 pub type Result<T> = std::result::Result<T, Error>;
 
-static VERSION_GROUPS: OnceLock<Vec<Vec<String>>> = OnceLock::new();
-
 /// Gets groups of versions that are considered minor updates in terms of mod compatibility
 ///
-/// This is determined by Modrinth's `major` parameter for game versions.
-pub async fn get_version_groups() -> Result<&'static Vec<Vec<String>>> {
-    if let Some(v) = VERSION_GROUPS.get() {
-        Ok(v)
-    } else {
-        let versions = MODRINTH_API.list_game_versions().await?;
-        let mut v = vec![vec![]];
-        for version in versions {
-            if version.version_type == GameVersionType::Release {
-                // Push the version to the latest group
-                v.last_mut().unwrap().push(version.version);
-                // Create a new group if a new major versions is present
-                if version.major {
-                    v.push(vec![]);
-                }
+/// This is determined by the game versions' `major` parameter, and the custom version groups in the `context`
+/// (which take precedence).
+pub async fn get_version_groups(context: &Context) -> Vec<Vec<String>> {
+    let custom = &context.version_groups;
+    let versions = &context.manifest().await.versions;
+    let mut v = vec![vec![]];
+    // Iterate in reverse chronological order
+    for version in versions.iter().rev() {
+        if version.version_type == GameVersionType::Release {
+            // Push the version to the latest group
+            v.last_mut().unwrap().push(version.version.clone());
+            // Create a new group if a new major versions is present
+            if version.major || custom.is_boundary(&version.version) {
+                v.push(vec![]);
             }
         }
-        let _ = VERSION_GROUPS.set(v);
-
-        Ok(VERSION_GROUPS.get().unwrap())
     }
+    custom.apply(v)
+}
+
+/// A filter that has been prepared to be run on many lists of files
+///
+/// Regexes are compiled, and version groups and loader compatibility are expanded ahead of time.
+#[derive(Debug, Clone)]
+pub enum CompiledFilter {
+    /// The loaders in order of preference, along with the game versions they are limited to
+    ModLoaderPrefer(Vec<(ModLoader, Vec<String>)>),
+    /// The compatible loaders, along with the game versions they are limited to
+    ModLoaderAny(Vec<(ModLoader, Vec<String>)>),
+    GameVersions(HashSet<String>),
+    GameVersionRange(GameVersionRange, Arc<Manifest>),
+    /// The releases that files' game versions should lead up to
+    TargetReleases(HashSet<String>, Arc<Manifest>),
+    ReleaseChannel(ReleaseChannel),
+    ReleasedBefore(DateTime<Utc>),
+    ReleasedAfter(DateTime<Utc>),
+    Filename(Regex),
+    Title(Regex),
+    Description(Regex),
+    ModVersion(ModVersionReq),
+    Environment(Environment),
+    Not(Box<CompiledFilter>),
+    AnyOf(Vec<CompiledFilter>),
+    /// Preference filters are stored after the other filters
+    AllOf(Vec<CompiledFilter>),
+    Weighted(Box<CompiledFilter>, u32),
 }
 
 impl Filter {
+    /// Check that the regexes and game version ranges in `self` are valid, without fetching anything
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Filter::GameVersionRange(range) => {
+                range.parse::<GameVersionRange>()?;
+            }
+            Filter::ModVersion(requirement) => {
+                requirement.parse::<ModVersionReq>()?;
+            }
+            Filter::Filename(regex) | Filter::Title(regex) | Filter::Description(regex) => {
+                Regex::new(regex)?;
+            }
+            Filter::Not(filter) | Filter::Weighted(filter, _) => filter.validate()?,
+            Filter::AnyOf(filters) | Filter::AllOf(filters) => {
+                for filter in filters {
+                    filter.validate()?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Get the symbolic game versions (e.g. `latest-release`) in `self` and the filters it contains
+    pub fn symbolic_versions(&self) -> Vec<String> {
+        match self {
+            Filter::GameVersionStrict(versions)
+            | Filter::GameVersionMinor(versions)
+            | Filter::GameVersionDevelopment(versions) => versions
+                .iter()
+                .filter(|v| SymbolicVersion::parse(v).is_some())
+                .cloned()
+                .collect_vec(),
+            Filter::GameVersionRange(range) => range
+                .parse::<GameVersionRange>()
+                .map(|range| {
+                    range
+                        .0
+                        .into_iter()
+                        .map(|comparator| comparator.version)
+                        .filter(|v| SymbolicVersion::parse(v).is_some())
+                        .collect_vec()
+                })
+                .unwrap_or_default(),
+            Filter::Not(filter) | Filter::Weighted(filter, _) => filter.symbolic_versions(),
+            Filter::AnyOf(filters) | Filter::AllOf(filters) => filters
+                .iter()
+                .flat_map(Filter::symbolic_versions)
+                .collect_vec(),
+            _ => vec![],
+        }
+    }
+
+    /// Get the game versions that `self` targets, which are the versions in the `GameVersion*` filters
+    /// that files have to pass
+    pub fn game_versions(&self) -> Vec<String> {
+        match self {
+            Filter::GameVersionStrict(versions)
+            | Filter::GameVersionMinor(versions)
+            | Filter::GameVersionDevelopment(versions) => versions.clone(),
+            Filter::AllOf(filters) => filters.iter().flat_map(Filter::game_versions).collect_vec(),
+            _ => vec![],
+        }
+    }
+
+    /// Prepare `self` to be run on many lists of files, using the loader compatibility rules and version groups in `context`
+    ///
+    /// This function fails if a regex, game version range, or mod version requirement fails to parse.
+    pub async fn compile(&self, context: &Context) -> Result<CompiledFilter> {
+        self.compile_for(context, &[]).await
+    }
+
+    /// Like [`Filter::compile`], but loader compatibility rules limited to some game versions
+    /// are only used if they apply to the target `game_versions`
+    ///
+    /// If `game_versions` is empty, these rules are checked against the game versions of each file instead.
+    pub async fn compile_for(
+        &self,
+        context: &Context,
+        game_versions: &[String],
+    ) -> Result<CompiledFilter> {
+        let rules = &context.loader_compatibility;
+        let expand = |loaders: &[ModLoader]| {
+            let expanded = compatibility::expand(rules, loaders);
+            if game_versions.is_empty() {
+                expanded
+            } else {
+                expanded
+                    .into_iter()
+                    .filter(|(_, scope)| scope_applies(scope, game_versions))
+                    .map(|(loader, _)| (loader, vec![]))
+                    .collect_vec()
+            }
+        };
+        Ok(match self {
+            Filter::ModLoaderPrefer(loaders) => CompiledFilter::ModLoaderPrefer(expand(loaders)),
+            Filter::ModLoaderAny(loaders) => CompiledFilter::ModLoaderAny(expand(loaders)),
+            Filter::GameVersionStrict(versions) => CompiledFilter::GameVersions(
+                resolve_versions(context, versions)
+                    .await
+                    .into_iter()
+                    .collect_hashset(),
+            ),
+            Filter::GameVersionMinor(versions) => {
+                let versions = &resolve_versions(context, versions).await;
+                let manifest = &context.manifest().await;
+                // Development versions are grouped with the release they lead up to
+                let targets = versions
+                    .iter()
+                    .filter_map(|v| game_version::target_release(v, Some(manifest)))
+                    .collect_hashset();
+                let mut final_versions = versions.iter().cloned().collect_hashset();
+                for group in get_version_groups(context).await {
+                    if group
+                        .iter()
+                        .any(|v| versions.contains(v) || targets.contains(v))
+                    {
+                        final_versions.extend(group);
+                    }
+                }
+                CompiledFilter::GameVersions(final_versions)
+            }
+            Filter::GameVersionRange(range) => {
+                let mut range = range.parse::<GameVersionRange>()?;
+                let manifest = context.manifest().await;
+                for comparator in &mut range.0 {
+                    if let Some(version) = SymbolicVersion::parse(&comparator.version)
+                        .and_then(|symbolic| symbolic.resolve(&manifest))
+                    {
+                        comparator.version = version;
+                    }
+                }
+                CompiledFilter::GameVersionRange(range, manifest)
+            }
+            Filter::GameVersionDevelopment(versions) => CompiledFilter::TargetReleases(
+                resolve_versions(context, versions)
+                    .await
+                    .into_iter()
+                    .collect_hashset(),
+                context.manifest().await,
+            ),
+            Filter::ReleaseChannel(channel) => CompiledFilter::ReleaseChannel(*channel),
+            Filter::MinimumAge(days) => {
+                CompiledFilter::ReleasedBefore(Utc::now() - TimeDelta::days((*days).into()))
+            }
+            Filter::ReleasedBefore(date) => CompiledFilter::ReleasedBefore(*date),
+            Filter::ReleasedAfter(date) => CompiledFilter::ReleasedAfter(*date),
+            Filter::Filename(regex) => CompiledFilter::Filename(Regex::new(regex)?),
+            Filter::Title(regex) => CompiledFilter::Title(Regex::new(regex)?),
+            Filter::Description(regex) => CompiledFilter::Description(Regex::new(regex)?),
+            Filter::ModVersion(requirement) => CompiledFilter::ModVersion(requirement.parse()?),
+            Filter::Environment(side) => CompiledFilter::Environment(*side),
+            Filter::Not(filter) => CompiledFilter::Not(Box::new(
+                compile_boxed(filter, context, game_versions).await?,
+            )),
+            Filter::Weighted(filter, weight) => CompiledFilter::Weighted(
+                Box::new(compile_boxed(filter, context, game_versions).await?),
+                *weight,
+            ),
+            Filter::AnyOf(filters) => {
+                let mut compiled = vec![];
+                for filter in filters {
+                    compiled.push(compile_boxed(filter, context, game_versions).await?);
+                }
+                CompiledFilter::AnyOf(compiled)
+            }
+            Filter::AllOf(filters) => {
+                let mut compiled = vec![];
+                // Run preference filters last
+                for filter in filters
+                    .iter()
+                    .filter(|f| !f.is_preference())
+                    .chain(filters.iter().filter(|f| f.is_preference()))
+                {
+                    compiled.push(compile_boxed(filter, context, game_versions).await?);
+                }
+                CompiledFilter::AllOf(compiled)
+            }
+        })
+    }
+
     /// Returns the indices of `download_files` that have successfully filtered through `self`
     ///
-    /// This function fails if getting version groups fails, or the regex files to parse.
+    /// This function fails if a regex, game version range, or mod version requirement fails to parse.
+    /// Use [`Filter::compile`] instead if `self` will be run on many lists of files.
     pub async fn filter(
         &self,
+        context: &Context,
         download_files: impl Iterator<Item = (usize, &Metadata)> + Clone,
     ) -> Result<HashSet<usize>> {
-        Ok(match self {
-            Filter::ModLoaderPrefer(loaders) => loaders
+        Ok(self.compile(context).await?.filter(download_files))
+    }
+}
+
+/// Replace the symbolic game versions in `versions` with the concrete versions they resolve to
+///
+/// Symbolic versions that can't be resolved are kept, so they don't match any files.
+async fn resolve_versions(context: &Context, versions: &[String]) -> Vec<String> {
+    if versions.iter().any(|v| SymbolicVersion::parse(v).is_some()) {
+        let manifest = context.manifest().await;
+        versions
+            .iter()
+            .map(|v| {
+                SymbolicVersion::parse(v)
+                    .and_then(|symbolic| symbolic.resolve(&manifest))
+                    .unwrap_or_else(|| v.clone())
+            })
+            .collect_vec()
+    } else {
+        versions.to_vec()
+    }
+}
+
+/// Compiles `filter` in a boxed future, so that filters can contain other filters
+fn compile_boxed<'a>(
+    filter: &'a Filter,
+    context: &'a Context,
+    game_versions: &'a [String],
+) -> BoxFuture<'a, Result<CompiledFilter>> {
+    Box::pin(filter.compile_for(context, game_versions))
+}
+
+impl CompiledFilter {
+    /// Returns the indices of `download_files` that have successfully filtered through `self`
+    pub fn filter<'a>(
+        &self,
+        download_files: impl Iterator<Item = (usize, &'a Metadata)> + Clone,
+    ) -> HashSet<usize> {
+        match self {
+            CompiledFilter::ModLoaderPrefer(loaders) => loaders
                 .iter()
-                .map(move |l| {
+                .map(move |(l, scope)| {
                     download_files
                         .clone()
-                        .positions(|f| f.loaders.contains(l))
+                        .positions(|f| {
+                            f.loaders.contains(l) && scope_applies(scope, &f.game_versions)
+                        })
                         .collect_hashset()
                 })
                 .find(|v| !v.is_empty())
                 .unwrap_or_default(),
 
-            Filter::ModLoaderAny(loaders) => download_files
-                .positions(|f| loaders.iter().any(|l| f.loaders.contains(l)))
+            CompiledFilter::ModLoaderAny(loaders) => download_files
+                .positions(|f| {
+                    loaders.iter().any(|(l, scope)| {
+                        f.loaders.contains(l) && scope_applies(scope, &f.game_versions)
+                    })
+                })
                 .collect_hashset(),
 
-            Filter::GameVersionStrict(versions) => download_files
-                .positions(|f| versions.iter().any(|vc| f.game_versions.contains(vc)))
+            CompiledFilter::GameVersions(versions) => download_files
+                .positions(|f| f.game_versions.iter().any(|v| versions.contains(v)))
                 .collect_hashset(),
 
-            Filter::GameVersionMinor(versions) => {
-                let mut final_versions = vec![];
-                for group in get_version_groups().await? {
-                    if group.iter().any(|v| versions.contains(v)) {
-                        final_versions.extend(group.clone());
-                    }
-                }
+            CompiledFilter::GameVersionRange(range, manifest) => download_files
+                .positions(|f| {
+                    f.game_versions
+                        .iter()
+                        .any(|v| range.contains(v, Some(&**manifest)))
+                })
+                .collect_hashset(),
 
-                download_files
-                    .positions(|f| final_versions.iter().any(|vc| f.game_versions.contains(vc)))
-                    .collect_hashset()
-            }
+            CompiledFilter::TargetReleases(releases, manifest) => download_files
+                .positions(|f| {
+                    f.game_versions.iter().any(|v| {
+                        game_version::target_release(v, Some(&**manifest))
+                            .is_some_and(|release| releases.contains(&release))
+                    })
+                })
+                .collect_hashset(),
 
-            Filter::ReleaseChannel(channel) => download_files
+            CompiledFilter::ReleaseChannel(channel) => download_files
                 .positions(|f| match channel {
                     ReleaseChannel::Alpha => true,
                     ReleaseChannel::Beta => {
@@ -98,49 +364,215 @@ impl Filter {
                 })
                 .collect_hashset(),
 
-            Filter::Filename(regex) => {
-                let regex = Regex::new(regex)?;
+            CompiledFilter::ReleasedBefore(date) => download_files
+                .positions(|f| f.published < *date)
+                .collect_hashset(),
+
+            CompiledFilter::ReleasedAfter(date) => download_files
+                .positions(|f| f.published > *date)
+                .collect_hashset(),
+
+            CompiledFilter::Filename(regex) => download_files
+                .positions(|f| regex.is_match(&f.filename))
+                .collect_hashset(),
+
+            CompiledFilter::Title(regex) => download_files
+                .positions(|f| regex.is_match(&f.title))
+                .collect_hashset(),
+
+            CompiledFilter::Description(regex) => download_files
+                .positions(|f| regex.is_match(&f.description))
+                .collect_hashset(),
+
+            CompiledFilter::ModVersion(requirement) => download_files
+                .positions(|f| {
+                    ModVersion::parse(&f.version_number, &f.game_versions)
+                        .is_some_and(|v| requirement.matches(&v))
+                })
+                .collect_hashset(),
+
+            CompiledFilter::Environment(side) => download_files
+                .positions(|f| f.environment.is_none_or(|e| e == *side))
+                .collect_hashset(),
+
+            CompiledFilter::Not(filter) => {
+                let excluded = filter.filter(download_files.clone());
                 download_files
-                    .positions(|f| regex.is_match(&f.filename))
+                    .map(|(i, _)| i)
+                    .filter(|i| !excluded.contains(i))
                     .collect_hashset()
             }
 
-            Filter::Title(regex) => {
-                let regex = Regex::new(regex)?;
-                download_files
-                    .positions(|f| regex.is_match(&f.title))
-                    .collect_hashset()
+            CompiledFilter::Weighted(filter, _) => filter.filter(download_files),
+
+            CompiledFilter::AnyOf(filters) => filters
+                .iter()
+                .flat_map(|filter| filter.filter(download_files.clone()))
+                .collect_hashset(),
+
+            CompiledFilter::AllOf(filters) => {
+                let download_files = download_files.collect_vec();
+                let mut indices = download_files.iter().map(|(i, _)| *i).collect_hashset();
+                for filter in filters {
+                    let remaining = download_files
+                        .iter()
+                        .filter(|(i, _)| indices.contains(i))
+                        .copied()
+                        .collect_vec();
+                    let selected = filter.filter(remaining.into_iter());
+                    indices.retain(|i| selected.contains(i));
+                }
+                indices
+            }
+        }
+    }
+}
+
+/// A list of filters that have been validated and prepared ahead of time,
+/// so that they can be run on the files of many mods without repeating work
+///
+/// These should be created once per profile.
+#[derive(Debug, Clone, Default)]
+pub struct CompiledFilters {
+    filters: Vec<(Filter, Arc<CompiledFilter>)>,
+    /// The game versions targeted by the filters, which loader compatibility rules are checked against
+    game_versions: Vec<String>,
+}
+
+impl CompiledFilters {
+    pub async fn new(context: &Context, filters: impl IntoIterator<Item = Filter>) -> Result<Self> {
+        Self::default().compile_extend(context, filters).await
+    }
+
+    /// Compile `filters` and add them after the filters in `self`
+    ///
+    /// Loader compatibility rules in `filters` are checked against the game versions targeted by `self`,
+    /// or by `filters` if `self` doesn't target any.
+    pub async fn compile_extend(
+        mut self,
+        context: &Context,
+        filters: impl IntoIterator<Item = Filter>,
+    ) -> Result<Self> {
+        let filters = filters.into_iter().collect_vec();
+        if self.game_versions.is_empty() {
+            self.game_versions = resolve_versions(
+                context,
+                &filters.iter().flat_map(Filter::game_versions).collect_vec(),
+            )
+            .await;
+        }
+        for filter in filters {
+            let compiled_filter = filter.compile_for(context, &self.game_versions).await?;
+            self.filters.push((filter, Arc::new(compiled_filter)));
+        }
+        Ok(self)
+    }
+
+    /// The original filters
+    pub fn filters(&self) -> impl Iterator<Item = &Filter> + Clone {
+        self.filters.iter().map(|(filter, _)| filter)
+    }
+
+    /// Retain only the filters for which `predicate` returns true
+    pub fn retain(&mut self, mut predicate: impl FnMut(&Filter) -> bool) {
+        self.filters.retain(|(filter, _)| predicate(filter));
+    }
+
+    /// Whether there are `Weighted` filters, which can select any file that passes the other filters
+    pub fn has_weighted(&self) -> bool {
+        self.filters()
+            .any(|filter| matches!(filter, Filter::Weighted(..)))
+    }
+
+    /// Whether the newest file that passes the filters is always selected,
+    /// which isn't the case if there are weighted or [preference](Filter::is_preference) filters
+    pub fn prefers_newest(&self) -> bool {
+        !self.has_weighted() && !self.filters().any(Filter::is_preference)
+    }
+
+    /// Get the indices of `download_files` that pass the filters which don't [use compatibility information](Filter::uses_compatibility),
+    /// so that only these files have to be inspected
+    pub fn prefilter<'a>(
+        &self,
+        download_files: impl Iterator<Item = &'a Metadata> + Clone,
+    ) -> HashSet<usize> {
+        let mut indices = (0..download_files.clone().count()).collect_hashset();
+        for (filter, compiled) in &self.filters {
+            if !filter.uses_compatibility() && !matches!(filter, Filter::Weighted(..)) {
+                let selected = compiled.filter(download_files.clone().enumerate());
+                indices.retain(|i| selected.contains(i));
             }
+        }
+        indices
+    }
 
-            Filter::Description(regex) => {
-                let regex = Regex::new(regex)?;
-                download_files
-                    .positions(|f| regex.is_match(&f.description))
-                    .collect_hashset()
+    /// Get the concrete game versions that the symbolic game versions in the filters resolved to,
+    /// or `None` if they couldn't be resolved
+    pub async fn resolved_versions(&self, context: &Context) -> Vec<(String, Option<String>)> {
+        let symbolic = self
+            .filters()
+            .flat_map(Filter::symbolic_versions)
+            .collect_vec();
+        if symbolic.is_empty() {
+            return vec![];
+        }
+
+        let manifest = context.manifest().await;
+        let mut resolved = vec![];
+        for version in symbolic {
+            if resolved.iter().all(|(v, _)| v != &version) {
+                let concrete = SymbolicVersion::parse(&version)
+                    .and_then(|symbolic| symbolic.resolve(&manifest));
+                resolved.push((version, concrete));
             }
-        })
+        }
+        resolved
+    }
+}
+
+impl FileOrder {
+    /// Sort `download_files` in this order, keeping the platform's order for files that compare equal
+    ///
+    /// Files without a parsable version number are placed last when sorting by version number.
+    pub fn sort<T>(&self, download_files: &mut [(Metadata, T)]) {
+        match self {
+            Self::Platform => {}
+            Self::PublishDate => download_files.sort_by_key(|(m, _)| Reverse(m.published)),
+            Self::VersionNumber => download_files.sort_by_cached_key(|(m, _)| {
+                Reverse(ModVersion::parse(&m.version_number, &m.game_versions))
+            }),
+            Self::Featured => download_files.sort_by_key(|(m, _)| !m.featured),
+        }
     }
 }
 
 /// Assumes that the provided `download_files` are sorted in the order of preference (e.g. chronological)
-pub async fn select_latest(
-    download_files: impl Iterator<Item = &Metadata> + Clone,
-    filters: Vec<Filter>,
+///
+/// If there are `Weighted` filters, the highest scoring file that passes the other filters is chosen instead.
+pub fn select_latest<'a>(
+    download_files: impl Iterator<Item = &'a Metadata> + Clone,
+    filters: &CompiledFilters,
 ) -> Result<usize> {
     let mut filter_results = vec![];
     let mut run_last = vec![];
+    let mut weighted = vec![];
 
-    for filter in &filters {
-        if let Filter::ModLoaderPrefer(_) = filter {
+    for (filter, compiled) in &filters.filters {
+        if let CompiledFilter::Weighted(compiled, weight) = compiled.as_ref() {
+            // Weighted filters don't eliminate files, so they're only run on the final candidates
+            weighted.push((compiled, *weight));
+        } else if filter.is_preference() {
             // ModLoaderPrefer has to be run last
             run_last.push((
                 filter,
-                filter.filter(download_files.clone().enumerate()).await?,
+                compiled,
+                compiled.filter(download_files.clone().enumerate()),
             ));
         } else {
             filter_results.push((
                 filter,
-                filter.filter(download_files.clone().enumerate()).await?,
+                compiled,
+                compiled.filter(download_files.clone().enumerate()),
             ));
         }
     }
@@ -148,7 +580,7 @@ pub async fn select_latest(
     let empty_filtrations = filter_results
         .iter()
         .chain(run_last.iter())
-        .filter_map(|(filter, indices)| {
+        .filter_map(|(filter, _, indices)| {
             if indices.is_empty() {
                 Some(filter.to_string())
             } else {
@@ -161,7 +593,7 @@ pub async fn select_latest(
     }
 
     // Get the indices of the filtrations
-    let mut filter_results = filter_results.into_iter().map(|(_, set)| set);
+    let mut filter_results = filter_results.into_iter().map(|(_, _, set)| set);
 
     // Intersect all the index_sets by folding the HashSet::intersection method
     // Ref: https://www.reddit.com/r/rust/comments/5v35l6/intersection_of_more_than_two_sets
@@ -183,23 +615,201 @@ pub async fn select_latest(
         }
     });
 
-    let mut filter_results = vec![];
-    for (filter, _) in run_last {
-        filter_results.push(filter.filter(download_files.clone()).await?)
-    }
-    let mut filter_results = filter_results.into_iter();
+    let mut filter_results = run_last
+        .into_iter()
+        .map(|(_, compiled, _)| compiled.filter(download_files.clone()));
 
-    let final_index = filter_results
+    let candidates = filter_results
         .next()
-        .and_then(|set_1| {
-            filter_results
-                .fold(set_1, |set_a, set_b| {
-                    set_a.intersection(&set_b).copied().collect_hashset()
-                })
-                .into_iter()
-                .min()
+        .map(|set_1| {
+            filter_results.fold(set_1, |set_a, set_b| {
+                set_a.intersection(&set_b).copied().collect_hashset()
+            })
+        })
+        .unwrap_or_else(|| final_indices.clone());
+
+    // Exclude auxiliary assets and only select secondary assets if there are no primary ones,
+    // unless the user is selecting files by their filename
+    let candidates = if filters.filters().any(Filter::selects_filename) {
+        candidates
+    } else {
+        let kinds = download_files
+            .clone()
+            .filter(|(i, _)| candidates.contains(i))
+            .map(|(i, f)| (i, f.asset_kind))
+            .collect_vec();
+        let best = kinds.iter().map(|(_, kind)| *kind).min();
+        if best == Some(AssetKind::Auxiliary) {
+            return Err(Error::OnlyAuxiliary);
+        }
+        kinds
+            .into_iter()
+            .filter_map(|(i, kind)| if Some(kind) == best { Some(i) } else { None })
+            .collect_hashset()
+    };
+
+    if weighted.is_empty() {
+        return candidates.into_iter().min().ok_or(Error::IntersectFailure);
+    }
+
+    // Score the remaining files using the weighted filters
+    let download_files = download_files.filter(|(i, _)| candidates.contains(i));
+    let weighted_results = weighted
+        .into_iter()
+        .map(|(compiled, weight)| (compiled.filter(download_files.clone()), weight))
+        .collect_vec();
+
+    let final_index = download_files
+        .max_by_key(|(i, f)| {
+            let score = weighted_results
+                .iter()
+                .filter(|(indices, _)| indices.contains(i))
+                .map(|(_, weight)| u64::from(*weight))
+                .sum::<u64>();
+            // Break ties using the publish date, and then the order of preference
+            (score, f.published, Reverse(*i))
         })
+        .map(|(i, _)| i)
         .ok_or(Error::IntersectFailure)?;
 
     Ok(final_index)
 }
+
+/// The results of running each filter on every candidate file,
+/// used to explain which filters eliminated which files when selection fails
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub filters: Vec<Filter>,
+    /// For every candidate file, whether each of the `filters` accepted it
+    pub candidates: Vec<Vec<bool>>,
+    /// The indices of the candidate files accepted by both filters, for every pair of `filters`
+    pub intersections: Vec<((usize, usize), HashSet<usize>)>,
+}
+
+impl Explanation {
+    /// The number of candidate files that the filter at `filter` accepted
+    pub fn accepted_count(&self, filter: usize) -> usize {
+        self.candidates
+            .iter()
+            .filter(|verdicts| verdicts[filter])
+            .count()
+    }
+
+    /// The filters that rejected the candidate file at `candidate`
+    pub fn rejected_by(&self, candidate: usize) -> impl Iterator<Item = &Filter> {
+        self.filters
+            .iter()
+            .zip(&self.candidates[candidate])
+            .filter_map(|(filter, accepted)| if *accepted { None } else { Some(filter) })
+    }
+
+    /// The pairs of filters that each accepted some candidate files, but accepted none in common
+    ///
+    /// These are the combinations that caused an `IntersectFailure`.
+    pub fn conflicts(&self) -> impl Iterator<Item = (&Filter, &Filter)> {
+        self.intersections.iter().filter_map(|((a, b), indices)| {
+            if indices.is_empty() && self.accepted_count(*a) != 0 && self.accepted_count(*b) != 0 {
+                Some((&self.filters[*a], &self.filters[*b]))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Run each of `filters` separately on all of `download_files` and return which files each filter accepted
+///
+/// Unlike `select_latest`, preference filters are run on all the files instead of those selected by the other filters.
+pub fn explain<'a>(
+    download_files: impl Iterator<Item = &'a Metadata> + Clone,
+    filters: &CompiledFilters,
+) -> Explanation {
+    let filter_results = filters
+        .filters
+        .iter()
+        .map(|(_, compiled)| compiled.filter(download_files.clone().enumerate()))
+        .collect_vec();
+
+    let candidates = (0..download_files.count())
+        .map(|i| {
+            filter_results
+                .iter()
+                .map(|set| set.contains(&i))
+                .collect_vec()
+        })
+        .collect_vec();
+
+    let mut intersections = vec![];
+    for (a, set_a) in filter_results.iter().enumerate() {
+        for (b, set_b) in filter_results.iter().enumerate().skip(a + 1) {
+            intersections.push(((a, b), set_a.intersection(set_b).copied().collect_hashset()));
+        }
+    }
+
+    Explanation {
+        filters: filters.filters().cloned().collect_vec(),
+        candidates,
+        intersections,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(game_versions: &[&str], loaders: &[ModLoader]) -> Metadata {
+        Metadata {
+            title: String::new(),
+            description: String::new(),
+            filename: "mod.jar".to_owned(),
+            version_number: "1.0.0".to_owned(),
+            published: DateTime::UNIX_EPOCH,
+            featured: false,
+            channel: ReleaseChannel::Release,
+            game_versions: game_versions.iter().map(|v| v.to_string()).collect(),
+            loaders: loaders.to_vec(),
+            environment: None,
+            asset_kind: AssetKind::Primary,
+        }
+    }
+
+    async fn select(files: &[Metadata], game_version: &str, loader: ModLoader) -> Result<usize> {
+        let filters = CompiledFilters::new(
+            &Context::default(),
+            [
+                Filter::ModLoaderPrefer(vec![loader]),
+                Filter::GameVersionStrict(vec![game_version.to_owned()]),
+            ],
+        )
+        .await?;
+        select_latest(files.iter(), &filters)
+    }
+
+    #[tokio::test]
+    async fn compatibility_scope_uses_target_versions() {
+        let files = [file(&["1.20.1", "1.20.4"], &[ModLoader::Forge])];
+
+        // NeoForge only runs Forge mods on 1.20.1
+        assert_eq!(
+            select(&files, "1.20.1", ModLoader::NeoForge).await.unwrap(),
+            0
+        );
+        assert!(matches!(
+            select(&files, "1.20.4", ModLoader::NeoForge).await,
+            Err(Error::FilterEmpty(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn compatibility_prefers_native_loader() {
+        let files = [
+            file(&["1.20.1"], &[ModLoader::Forge]),
+            file(&["1.20.1"], &[ModLoader::NeoForge]),
+        ];
+        assert_eq!(
+            select(&files, "1.20.1", ModLoader::NeoForge).await.unwrap(),
+            1
+        );
+        assert_eq!(select(&files, "1.20.1", ModLoader::Forge).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,205 @@
+use super::{check::CompiledFilters, AssetKind, Metadata};
+use crate::{
+    config::{
+        structs::{Environment, ModLoader},
+        Context,
+    },
+    game_version::Manifest,
+    iter_ext::IterExt as _,
+    jar_metadata::{self, read_jar_from},
+};
+use futures_util::{future, stream, StreamExt as _, TryStreamExt as _};
+use octocrab::Octocrab;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::Cursor,
+    path::Path,
+    sync::{Mutex, PoisonError},
+};
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub enum Error {
+    OctocrabError(#[from] octocrab::Error),
+}
+type Result<T> = std::result::Result<T, Error>;
+
+/// The compatibility information read from a GitHub release asset's mod descriptor
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct InspectedAsset {
+    pub loaders: Vec<ModLoader>,
+    /// The game version requirements, in the format the descriptor uses
+    pub game_versions: Vec<String>,
+    pub environment: Option<Environment>,
+    /// Whether the JAR file contains a mod descriptor
+    pub has_descriptor: bool,
+}
+
+/// The name of the file in the cache directory that inspected assets are cached in
+pub const CACHE_FILE: &str = "github_assets.json";
+
+/// How many assets are downloaded at the same time
+const CONCURRENT_DOWNLOADS: usize = 4;
+
+/// The inspected assets by asset ID, which are read from the cache when they're first needed
+///
+/// Assets whose JAR files couldn't be read are stored as `None`, so that they aren't downloaded again.
+#[derive(Debug, Default)]
+pub struct InspectedAssets(Mutex<Option<HashMap<u64, Option<InspectedAsset>>>>);
+
+impl InspectedAssets {
+    /// Run `f` on the inspected assets, which have to be loaded
+    fn with<T>(&self, f: impl FnOnce(&mut HashMap<u64, Option<InspectedAsset>>) -> T) -> T {
+        f(self
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert_with(HashMap::new))
+    }
+
+    /// Read the inspected assets from the cache at `path` if they haven't been loaded yet
+    fn load(&self, path: Option<&Path>) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert_with(|| {
+                path.and_then(|path| fs::read_to_string(path).ok())
+                    .and_then(|cache| serde_json::from_str(&cache).ok())
+                    .unwrap_or_default()
+            });
+    }
+}
+
+/// Download the asset with `asset_id` in the repository `owner/repo` using the REST client `github`,
+/// and read its mod descriptor
+///
+/// Returns `None` if the asset isn't a readable JAR file.
+pub async fn inspect_asset(
+    github: &Octocrab,
+    (owner, repo): (&str, &str),
+    asset_id: u64,
+) -> Result<Option<InspectedAsset>> {
+    let bytes = github
+        .repos(owner, repo)
+        .release_assets()
+        .stream(asset_id)
+        .await?
+        .try_fold(Vec::new(), |mut bytes, chunk| {
+            bytes.extend_from_slice(&chunk);
+            future::ok(bytes)
+        })
+        .await?;
+    let Ok(mods) = read_jar_from(Cursor::new(bytes)) else {
+        return Ok(None);
+    };
+    Ok(Some(InspectedAsset {
+        loaders: mods
+            .iter()
+            .flat_map(|m| m.loaders.iter().cloned())
+            .collect_unique(),
+        game_versions: mods
+            .iter()
+            .flat_map(|m| m.game_versions.iter().cloned())
+            .collect_vec(),
+        environment: mods.iter().find_map(|m| m.environment),
+        has_descriptor: !mods.is_empty(),
+    }))
+}
+
+/// Inspect the primary `assets` of the repository `owner/repo` that haven't been inspected before,
+/// and apply the results to their metadata
+///
+/// Assets are downloaded using the REST client `github`, a few at a time, and the results are stored in `context`.
+/// They're also written to the cache in the context's cache directory, if it's set, once all of them have been inspected.
+/// Only the assets that pass `filters`' checks which don't use compatibility information are inspected.
+pub async fn inspect_assets<'a>(
+    context: &Context,
+    github: &Octocrab,
+    repo: (&str, &str),
+    assets: impl IntoIterator<Item = (&'a mut Metadata, u64)>,
+    filters: &CompiledFilters,
+) {
+    let assets = assets
+        .into_iter()
+        .filter(|(metadata, _)| metadata.asset_kind == AssetKind::Primary)
+        .collect_vec();
+    let candidates = filters.prefilter(assets.iter().map(|(metadata, _)| &**metadata));
+    let assets = assets
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, asset)| candidates.contains(&i).then_some(asset))
+        .collect_vec();
+
+    let inspected_assets = &context.inspected_assets;
+    let cache_path = context.cache_dir.as_ref().map(|dir| dir.join(CACHE_FILE));
+    inspected_assets.load(cache_path.as_deref());
+    let uncached = inspected_assets.with(|cache| {
+        assets
+            .iter()
+            .map(|(_, id)| *id)
+            .filter(|id| !cache.contains_key(id))
+            .collect_hashset()
+    });
+    if !uncached.is_empty() {
+        let inspected = stream::iter(uncached)
+            .map(|id| async move { (id, inspect_asset(github, repo, id).await) })
+            .buffer_unordered(CONCURRENT_DOWNLOADS)
+            .collect::<Vec<_>>()
+            .await;
+
+        // Assets that failed to download are inspected again next time
+        let json = inspected_assets.with(|cache| {
+            cache.extend(
+                inspected
+                    .into_iter()
+                    .filter_map(|(id, result)| Some((id, result.ok()?))),
+            );
+            serde_json::to_string(cache)
+        });
+        if let (Some(path), Ok(json)) = (cache_path, json) {
+            if let Some(dir) = path.parent() {
+                let _ = fs::create_dir_all(dir);
+            }
+            let _ = fs::write(path, json);
+        }
+    }
+
+    let manifest = context.manifest().await;
+    for (metadata, id) in assets {
+        let inspected = inspected_assets.with(|cache| cache.get(&id).cloned().flatten());
+        if let Some(inspected) = inspected {
+            apply(metadata, inspected, &manifest);
+        }
+    }
+}
+
+/// Replace the loaders, game versions, and environment guessed from the filename in `metadata`
+/// with the ones read from the asset's mod descriptor
+///
+/// Assets without a descriptor are demoted to secondary, since they're most likely libraries.
+fn apply(metadata: &mut Metadata, inspected: InspectedAsset, manifest: &Manifest) {
+    if !inspected.has_descriptor {
+        metadata.asset_kind = AssetKind::Secondary;
+        return;
+    }
+
+    if !inspected.loaders.is_empty() {
+        metadata.loaders = inspected.loaders;
+    }
+    if !inspected.game_versions.is_empty() {
+        metadata.game_versions = manifest
+            .versions
+            .iter()
+            .filter(|v| {
+                inspected
+                    .game_versions
+                    .iter()
+                    .any(|r| jar_metadata::requirement_matches(r, &v.version, Some(manifest)))
+            })
+            .map(|v| v.version.clone())
+            .collect_vec();
+    }
+    metadata.environment = inspected.environment.or(metadata.environment);
+}
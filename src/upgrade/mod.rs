@@ -1,16 +1,20 @@
 pub mod check;
+pub mod inspect;
 pub mod mod_downloadable;
 pub mod modpack_downloadable;
 
 use crate::{
     config::{
         filters::ReleaseChannel,
-        structs::{ModIdentifier, ModLoader},
+        structs::{Environment, ModIdentifier, ModLoader},
     },
+    game_version::{self, Manifest},
+    gitlab::Release as GLRelease,
     iter_ext::IterExt as _,
     modpack::modrinth::structs::ModpackFile as ModpackModFile,
     version_ext::VersionExt,
 };
+use chrono::{DateTime, Utc};
 use ferinth::structures::version::{
     DependencyType as MRDependencyType, Version as MRVersion, VersionType,
 };
@@ -18,7 +22,7 @@ use furse::structures::file_structs::{
     File as CFFile, FileRelationType as CFFileRelationType, FileReleaseType,
 };
 use octocrab::models::repos::{Asset as GHAsset, Release as GHRelease};
-use reqwest::{Client, Url};
+use reqwest::{header::HeaderMap, Client, Url};
 use std::{
     fs::{create_dir_all, rename, OpenOptions},
     io::{BufWriter, Write},
@@ -41,11 +45,70 @@ pub struct Metadata {
     /// The body of the GitHub Release, or the changelog of the Modrinth Version
     pub description: String,
     pub filename: String,
+    /// The tag of the GitHub Release, the version number of the Modrinth Version,
+    /// or the display name of the CurseForge File
+    ///
+    /// This can be parsed leniently using [`ModVersion`](crate::mod_version::ModVersion).
+    pub version_number: String,
+    /// When the GitHub Release, Modrinth Version, or CurseForge File was published
+    pub published: DateTime<Utc>,
+    /// Whether the Modrinth Version is featured
+    pub featured: bool,
 
     pub channel: ReleaseChannel,
 
     pub game_versions: Vec<String>,
     pub loaders: Vec<ModLoader>,
+    /// The side(s) of the game this file runs on, if it is known
+    pub environment: Option<Environment>,
+    /// Whether this file is likely to be the mod itself, only relevant for GitHub release assets
+    pub asset_kind: AssetKind,
+}
+
+/// How likely a file is to be the mod itself, rather than an auxiliary artifact released alongside it
+///
+/// Files are preferred in the order of the variants.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AssetKind {
+    /// A JAR file that is most likely the mod
+    #[default]
+    Primary,
+    /// A file that might be the mod, such as a ZIP archive
+    Secondary,
+    /// A file that isn't the mod, such as a sources, javadoc, or development JAR, or a checksum or signature
+    Auxiliary,
+}
+
+/// The last token of JAR filenames that aren't the mod (e.g. `sodium-0.5.8-sources.jar`)
+const AUXILIARY_JAR_SUFFIXES: [&str; 7] = [
+    "sources", "source", "src", "javadoc", "docs", "dev", "deobf",
+];
+/// The extensions of checksum and signature files
+const AUXILIARY_EXTENSIONS: [&str; 7] = ["sha1", "sha256", "sha512", "md5", "asc", "sig", "pom"];
+const JAR_CONTENT_TYPES: [&str; 2] = ["application/java-archive", "application/x-java-archive"];
+
+impl AssetKind {
+    /// Classify a GitHub release asset using its `filename` and `content_type`
+    pub fn classify(filename: &str, content_type: &str) -> Self {
+        let filename = filename.to_lowercase();
+        let (stem, extension) = filename.rsplit_once('.').unwrap_or((&filename, ""));
+
+        if AUXILIARY_EXTENSIONS.contains(&extension) {
+            Self::Auxiliary
+        } else if extension == "jar" || JAR_CONTENT_TYPES.contains(&content_type) {
+            if stem
+                .rsplit(['-', '_', '+', '.'])
+                .next()
+                .is_some_and(|token| AUXILIARY_JAR_SUFFIXES.contains(&token))
+            {
+                Self::Auxiliary
+            } else {
+                Self::Primary
+            }
+        } else {
+            Self::Secondary
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -56,11 +119,15 @@ pub struct DownloadData {
     /// The filename by default, but can be configured with subdirectories for modpacks.
     pub output: PathBuf,
     /// The length of the file in bytes
+    ///
+    /// This is 0 if it's unknown (e.g. for GitLab release links), in which case the `Content-Length` is used when downloading.
     pub length: usize,
     /// The dependencies this file has
     pub dependencies: Vec<ModIdentifier>,
     /// Other mods this file is incompatible with
     pub conflicts: Vec<ModIdentifier>,
+    /// Additional headers to send when downloading the file, such as a token for private GitLab projects
+    pub headers: HeaderMap,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -68,24 +135,57 @@ pub struct DownloadData {
 /// Contains the mod ID and file ID
 pub struct DistributionDeniedError(pub i32, pub i32);
 
+/// The CurseForge game version type that mod loaders are listed under
+const CF_LOADER_TYPE_ID: i32 = 68441;
+/// The CurseForge game version type that the client and server environments are listed under
+const CF_ENVIRONMENT_TYPE_ID: i32 = 75208;
+
+/// Get the environment of a CurseForge file from its game versions, if it is listed
+pub fn cf_file_environment(file: &CFFile) -> Option<Environment> {
+    let sides = file
+        .sortable_game_versions
+        .iter()
+        .filter(|v| v.game_version_type_id == Some(CF_ENVIRONMENT_TYPE_ID))
+        .map(|v| v.game_version_name.to_lowercase())
+        .collect_hashset();
+    match (sides.contains("client"), sides.contains("server")) {
+        (true, true) => Some(Environment::Both),
+        (true, false) => Some(Environment::Client),
+        (false, true) => Some(Environment::Server),
+        (false, false) => None,
+    }
+}
+
 pub fn try_from_cf_file(
     file: CFFile,
 ) -> std::result::Result<(Metadata, DownloadData), DistributionDeniedError> {
     Ok((
         Metadata {
+            environment: cf_file_environment(&file),
+            version_number: file.display_name.clone(),
             title: file.display_name,
             description: String::new(), // Changelog requires a separate request
             filename: file.file_name.clone(),
+            published: file.file_date,
+            featured: false,
+            asset_kind: AssetKind::Primary,
             channel: match file.release_type {
                 FileReleaseType::Release => ReleaseChannel::Release,
                 FileReleaseType::Beta => ReleaseChannel::Beta,
                 FileReleaseType::Alpha => ReleaseChannel::Alpha,
             },
             loaders: file
-                .game_versions
+                .sortable_game_versions
                 .iter()
-                .filter_map(|s| ModLoader::from_str(s).ok())
-                .collect_vec(),
+                .filter(|v| v.game_version_type_id == Some(CF_LOADER_TYPE_ID))
+                .map(|v| ModLoader::from(v.game_version_name.as_str()))
+                .chain(
+                    // Fallback for files without sortable game versions
+                    file.game_versions
+                        .iter()
+                        .filter_map(|s| ModLoader::from_str(s).ok()),
+                )
+                .collect_unique(),
             game_versions: file.game_versions,
         },
         DownloadData {
@@ -116,6 +216,7 @@ pub fn try_from_cf_file(
                     }
                 })
                 .collect_vec(),
+            headers: HeaderMap::new(),
         },
     ))
 }
@@ -126,6 +227,9 @@ pub fn from_mr_version(version: MRVersion) -> (Metadata, DownloadData) {
             title: version.name.clone(),
             description: version.changelog.as_ref().cloned().unwrap_or_default(),
             filename: version.get_version_file().filename.clone(),
+            version_number: version.version_number.clone(),
+            published: version.date_published,
+            featured: version.featured,
             channel: match version.version_type {
                 VersionType::Release => ReleaseChannel::Release,
                 VersionType::Beta => ReleaseChannel::Beta,
@@ -134,9 +238,10 @@ pub fn from_mr_version(version: MRVersion) -> (Metadata, DownloadData) {
             loaders: version
                 .loaders
                 .iter()
-                .filter_map(|s| ModLoader::from_str(s).ok())
+                .map(|s| ModLoader::from(s.as_str()))
                 .collect_vec(),
-
+            environment: None, // Only available from the project
+            asset_kind: AssetKind::Primary,
             game_versions: version.game_versions.clone(),
         },
         DownloadData {
@@ -184,6 +289,7 @@ pub fn from_mr_version(version: MRVersion) -> (Metadata, DownloadData) {
                     }
                 })
                 .collect_vec(),
+            headers: HeaderMap::new(),
         },
     )
 }
@@ -199,11 +305,13 @@ pub fn from_modpack_file(file: ModpackModFile) -> DownloadData {
         length: file.file_size,
         dependencies: Vec::new(),
         conflicts: Vec::new(),
+        headers: HeaderMap::new(),
     }
 }
 
 pub fn from_gh_releases(
     releases: impl IntoIterator<Item = GHRelease>,
+    manifest: &Manifest,
 ) -> Vec<(Metadata, DownloadData)> {
     releases
         .into_iter()
@@ -213,24 +321,23 @@ pub fn from_gh_releases(
                     Metadata {
                         title: release.name.clone().unwrap_or_default(),
                         description: release.body.clone().unwrap_or_default(),
+                        version_number: release.tag_name.clone(),
+                        published: release.published_at.unwrap_or(asset.created_at),
+                        featured: false,
                         channel: if release.prerelease {
                             ReleaseChannel::Beta
                         } else {
                             ReleaseChannel::Release
                         },
-                        game_versions: asset
-                            .name
-                            .trim_end_matches(".jar")
-                            .split(['-', '_', '+'])
-                            .map(|s| s.trim_start_matches("mc"))
-                            .map(ToOwned::to_owned)
-                            .collect_vec(),
+                        game_versions: game_version::versions_in_filename(&asset.name, manifest),
                         loaders: asset
                             .name
                             .trim_end_matches(".jar")
                             .split(['-', '_', '+'])
                             .filter_map(|s| ModLoader::from_str(s).ok())
                             .collect_vec(),
+                        environment: None,
+                        asset_kind: AssetKind::classify(&asset.name, &asset.content_type),
                         filename: asset.name.clone(),
                     },
                     DownloadData {
@@ -239,6 +346,7 @@ pub fn from_gh_releases(
                         length: asset.size as usize,
                         dependencies: Vec::new(),
                         conflicts: Vec::new(),
+                        headers: HeaderMap::new(),
                     },
                 )
             })
@@ -246,6 +354,67 @@ pub fn from_gh_releases(
         .collect_vec()
 }
 
+pub fn from_gl_releases(
+    releases: impl IntoIterator<Item = GLRelease>,
+    manifest: &Manifest,
+) -> Vec<(Metadata, DownloadData)> {
+    releases
+        .into_iter()
+        // Upcoming releases haven't been released yet
+        .filter(|release| !release.upcoming_release)
+        .flat_map(|release| {
+            release.assets.links.into_iter().filter_map(move |link| {
+                // The link's name is a label chosen by the publisher, so the filename is taken from the URL
+                let download_url = link.direct_asset_url.unwrap_or(link.url);
+                let filename = url_filename(&download_url)?;
+                Some((
+                    Metadata {
+                        title: release.name.clone().unwrap_or_default(),
+                        description: release.description.clone().unwrap_or_default(),
+                        version_number: release.tag_name.clone(),
+                        published: release.released_at.unwrap_or(release.created_at),
+                        featured: false,
+                        // GitLab releases can't be marked as pre-releases
+                        channel: ReleaseChannel::Release,
+                        game_versions: game_version::versions_in_filename(&filename, manifest),
+                        loaders: filename
+                            .trim_end_matches(".jar")
+                            .split(['-', '_', '+'])
+                            .filter_map(|s| ModLoader::from_str(s).ok())
+                            .collect_vec(),
+                        environment: None,
+                        asset_kind: AssetKind::classify(&filename, ""),
+                        filename: filename.clone(),
+                    },
+                    DownloadData {
+                        download_url,
+                        output: filename.into(),
+                        length: 0, // Not provided by GitLab
+                        dependencies: Vec::new(),
+                        conflicts: Vec::new(),
+                        headers: HeaderMap::new(),
+                    },
+                ))
+            })
+        })
+        .collect_vec()
+}
+
+/// Get the filename from the last path segment of `url`,
+/// or `None` if there isn't one or it could escape the output directory
+fn url_filename(url: &Url) -> Option<String> {
+    let filename = url.path_segments()?.next_back()?;
+    if filename.is_empty()
+        || filename == "."
+        || filename.contains(['/', '\\'])
+        || filename.contains("..")
+    {
+        None
+    } else {
+        Some(filename.to_owned())
+    }
+}
+
 pub fn from_gh_asset(asset: GHAsset) -> DownloadData {
     DownloadData {
         download_url: asset.browser_download_url,
@@ -253,6 +422,7 @@ pub fn from_gh_asset(asset: GHAsset) -> DownloadData {
         length: asset.size as usize,
         dependencies: Vec::new(),
         conflicts: Vec::new(),
+        headers: HeaderMap::new(),
     }
 }
 
@@ -268,7 +438,16 @@ impl DownloadData {
         output_dir: impl AsRef<Path>,
         update: impl Fn(usize) + Send,
     ) -> Result<(usize, String)> {
-        let (filename, url, size) = (self.filename(), self.download_url, self.length);
+        let filename = self.filename();
+        let mut response = client
+            .get(self.download_url)
+            .headers(self.headers)
+            .send()
+            .await?;
+        let size = match self.length {
+            0 => response.content_length().unwrap_or_default() as usize,
+            length => length,
+        };
         let out_file_path = output_dir.as_ref().join(&self.output);
         let temp_file_path = out_file_path.with_extension("part");
         if let Some(up_dir) = out_file_path.parent() {
@@ -283,8 +462,6 @@ impl DownloadData {
                 .open(&temp_file_path)?,
         );
 
-        let mut response = client.get(url).send().await?;
-
         while let Some(chunk) = response.chunk().await? {
             temp_file.write_all(&chunk)?;
             update(chunk.len());
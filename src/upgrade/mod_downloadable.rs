@@ -1,15 +1,18 @@
 use super::{
-    from_gh_asset, from_gh_releases, from_mr_version, try_from_cf_file, DistributionDeniedError,
-    DownloadData,
+    check::CompiledFilters, from_gh_asset, from_gh_releases, from_gl_releases, from_mr_version,
+    inspect, try_from_cf_file, AssetKind, DistributionDeniedError, DownloadData, Metadata,
 };
 use crate::{
     config::{
-        filters::Filter,
+        filters::{FileOrder, Filter},
+        github_hosts, gitlab_hosts,
         structs::{Mod, ModIdentifier},
+        Context,
     },
     iter_ext::IterExt as _,
-    CURSEFORGE_API, GITHUB_API, MODRINTH_API,
+    CURSEFORGE_API, MODRINTH_API,
 };
+use octocrab::{models::repos::Release as GHRelease, Octocrab};
 use std::cmp::Reverse;
 
 #[derive(Debug, thiserror::Error)]
@@ -25,13 +28,67 @@ pub enum Error {
     CurseForgeError(#[from] furse::Error),
     #[error("GitHub: {0:#?}")]
     GitHubError(#[from] octocrab::Error),
+    GitHubHost(#[from] github_hosts::Error),
+    #[error("GitLab: {0}")]
+    GitLabError(#[from] crate::gitlab::Error),
+    GitLabHost(#[from] gitlab_hosts::Error),
 }
 type Result<T> = std::result::Result<T, Error>;
 
 impl Mod {
+    /// Get the filters to select this mod's files with, which are `profile_filters` followed by the mod's filters
+    /// unless the mod overrides them
+    async fn compiled_filters(
+        &self,
+        context: &Context,
+        profile_filters: &CompiledFilters,
+    ) -> Result<CompiledFilters> {
+        Ok(if self.override_filters {
+            CompiledFilters::new(context, self.all_filters()).await?
+        } else {
+            let mut filters = profile_filters.clone();
+            filters.retain(|filter| self.content_type.uses_profile_filter(filter));
+            filters.compile_extend(context, self.all_filters()).await?
+        })
+    }
+
+    /// Get the filters to select the file of a pinned release with,
+    /// which are the filename regex `asset` if provided, otherwise the mod's filters
+    async fn pinned_filters(
+        &self,
+        context: &Context,
+        asset: Option<&str>,
+        profile_filters: &CompiledFilters,
+    ) -> Result<CompiledFilters> {
+        Ok(match asset {
+            Some(pattern) => {
+                CompiledFilters::new(context, [Filter::Filename(pattern.to_owned())]).await?
+            }
+            None => self.compiled_filters(context, profile_filters).await?,
+        })
+    }
+
+    /// Select the file of a pinned release from `files` using `filters`
+    fn select_pinned(
+        &self,
+        mut files: Vec<(Metadata, DownloadData)>,
+        filters: &CompiledFilters,
+    ) -> Result<DownloadData> {
+        for (metadata, _) in &mut files {
+            metadata.environment = metadata.environment.or(self.environment);
+        }
+        let index = super::check::select_latest(files.iter().map(|(m, _)| m), filters)?;
+        Ok(files.into_iter().nth(index).unwrap().1)
+    }
+
+    /// Fetch the latest compatible file, sorting the files using the mod's file order if set, otherwise `profile_order`
+    ///
+    /// The settings from the config are taken from `context`.
     pub async fn fetch_download_file(
         &self,
-        mut profile_filters: Vec<Filter>,
+        context: &Context,
+        profile_filters: &CompiledFilters,
+        profile_order: FileOrder,
     ) -> Result<DownloadData> {
         match &self.identifier {
             ModIdentifier::PinnedCurseForgeProject(mod_id, pin) => {
@@ -41,14 +98,62 @@ impl Mod {
                 Ok(from_mr_version(MODRINTH_API.get_version(pin).await?).1)
             }
             ModIdentifier::PinnedGitHubRepository((owner, repo), pin) => Ok(from_gh_asset(
-                GITHUB_API
+                context
+                    .github_hosts
+                    .client(None, owner, repo)?
+                    .rest
                     .repos(owner, repo)
                     .release_assets()
                     .get(*pin as u64)
                     .await?,
             )),
+            ModIdentifier::PinnedGitHubRelease {
+                repo: (owner, repo),
+                tag,
+                asset,
+                host,
+            } => {
+                let github = context
+                    .github_hosts
+                    .client(host.as_deref(), owner, repo)?
+                    .rest;
+                let release = github.repos(owner, repo).releases().get_by_tag(tag).await?;
+                let filters = self
+                    .pinned_filters(context, asset.as_deref(), profile_filters)
+                    .await?;
+                let files = from_gh_releases_inspected(
+                    context,
+                    &github,
+                    (owner, repo),
+                    vec![release],
+                    &filters,
+                )
+                .await;
+                self.select_pinned(files, &filters)
+            }
+            ModIdentifier::PinnedGitLabRelease {
+                project,
+                tag,
+                asset,
+                host,
+            } => {
+                let gitlab = context.gitlab_hosts.client(host.as_deref())?;
+                let release = gitlab.get_release(project, tag).await?;
+                let filters = self
+                    .pinned_filters(context, asset.as_deref(), profile_filters)
+                    .await?;
+                let mut file = self.select_pinned(
+                    from_gl_releases([release], &*context.manifest().await),
+                    &filters,
+                )?;
+                file.headers = gitlab.download_headers(&file.download_url);
+                Ok(file)
+            }
             id => {
-                let download_files = match &id {
+                let filters = self.compiled_filters(context, profile_filters).await?;
+                let file_order = self.file_order.unwrap_or(profile_order);
+
+                let mut download_files = match &id {
                     ModIdentifier::CurseForgeProject(id) => {
                         let mut files = CURSEFORGE_API.get_mod_files(*id).await?;
                         files.sort_unstable_by_key(|f| Reverse(f.file_date));
@@ -63,28 +168,119 @@ impl Mod {
                         .into_iter()
                         .map(from_mr_version)
                         .collect_vec(),
-                    ModIdentifier::GitHubRepository(owner, repo) => GITHUB_API
-                        .repos(owner, repo)
-                        .releases()
-                        .list()
-                        .send()
-                        .await
-                        .map(|r| from_gh_releases(r.items))?,
+                    ModIdentifier::GitHubRepository(owner, repo)
+                    | ModIdentifier::GitHubHostRepository {
+                        repo: (owner, repo),
+                        ..
+                    } => {
+                        let host = match &id {
+                            ModIdentifier::GitHubHostRepository { host, .. } => Some(host.as_str()),
+                            _ => None,
+                        };
+                        let github = context.github_hosts.client(host, owner, repo)?.rest;
+                        // Releases are listed newest first, so the pages fetched so far are enough
+                        // if the files aren't reordered and older files can't be preferred
+                        let early_stop = file_order.is_platform() && filters.prefers_newest();
+                        let mut files = Vec::new();
+                        let mut page = Some(
+                            github
+                                .repos(owner, repo)
+                                .releases()
+                                .list()
+                                .per_page(100)
+                                .send()
+                                .await?,
+                        );
+                        while let Some(releases) = page {
+                            let mut new_files = from_gh_releases_inspected(
+                                context,
+                                &github,
+                                (owner, repo),
+                                releases.items,
+                                &filters,
+                            )
+                            .await;
+                            for (metadata, _) in &mut new_files {
+                                metadata.environment = metadata.environment.or(self.environment);
+                            }
+                            files.extend(new_files);
+                            if early_stop && selects_primary(&files, &filters) {
+                                break;
+                            }
+                            page = github.get_page(&releases.next).await?;
+                        }
+                        files
+                    }
+                    ModIdentifier::GitLabProject { project, host } => {
+                        let gitlab = context.gitlab_hosts.client(host.as_deref())?;
+                        // Releases are listed newest first, like GitHub's
+                        let early_stop = file_order.is_platform() && filters.prefers_newest();
+                        let mut files = Vec::new();
+                        let mut page = Some(1);
+                        while let Some(number) = page {
+                            let (releases, next_page) =
+                                gitlab.list_releases(project, number).await?;
+                            let mut new_files =
+                                from_gl_releases(releases, &*context.manifest().await);
+                            for (metadata, data) in &mut new_files {
+                                metadata.environment = metadata.environment.or(self.environment);
+                                data.headers = gitlab.download_headers(&data.download_url);
+                            }
+                            files.extend(new_files);
+                            if early_stop && selects_primary(&files, &filters) {
+                                break;
+                            }
+                            page = next_page;
+                        }
+                        files
+                    }
                     _ => unreachable!(),
                 };
+                file_order.sort(&mut download_files);
+                // Files without an environment (e.g. Modrinth versions) use the mod's environment
+                for (metadata, _) in &mut download_files {
+                    metadata.environment = metadata.environment.or(self.environment);
+                }
 
-                let index = super::check::select_latest(
-                    download_files.iter().map(|(m, _)| m),
-                    if self.override_filters {
-                        self.filters.clone()
-                    } else {
-                        profile_filters.extend(self.filters.clone());
-                        profile_filters
-                    },
-                )
-                .await?;
+                let index =
+                    super::check::select_latest(download_files.iter().map(|(m, _)| m), &filters)?;
                 Ok(download_files.into_iter().nth(index).unwrap().1)
             }
         }
     }
 }
+
+/// Whether a primary file (e.g. a JAR rather than a ZIP archive) is selected from `files`,
+/// which a file on a later page can't be preferred over
+fn selects_primary(files: &[(Metadata, DownloadData)], filters: &CompiledFilters) -> bool {
+    super::check::select_latest(files.iter().map(|(m, _)| m), filters)
+        .is_ok_and(|index| files[index].0.asset_kind == AssetKind::Primary)
+}
+
+/// Get the files of the assets in `releases` of the repository `owner/repo`,
+/// inspecting the ones that could pass `filters` using the REST client `github` if enabled
+pub(crate) async fn from_gh_releases_inspected(
+    context: &Context,
+    github: &Octocrab,
+    repo: (&str, &str),
+    releases: Vec<GHRelease>,
+    filters: &CompiledFilters,
+) -> Vec<(Metadata, DownloadData)> {
+    let ids = releases
+        .iter()
+        .flat_map(|r| &r.assets)
+        .map(|a| a.id.into_inner())
+        .collect_vec();
+    let mut files = from_gh_releases(releases, &*context.manifest().await);
+    if context.inspect_github_assets {
+        inspect::inspect_assets(
+            context,
+            github,
+            repo,
+            files.iter_mut().map(|(metadata, _)| metadata).zip(ids),
+            filters,
+        )
+        .await;
+    }
+    files
+}